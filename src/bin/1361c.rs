@@ -3,16 +3,15 @@ use std::io;
 fn read_line() -> String {
     let mut line = String::new();
     io::stdin().read_line(&mut line).unwrap();
-    return line.trim().to_string();
+    line.trim().to_string()
 }
 
 fn read_ints() -> Vec<usize> {
     let line = read_line();
-    return line
-        .split(" ")
-        .filter(|&s| s.len() > 0)
+    line.split(" ")
+        .filter(|&s| !s.is_empty())
         .map(|s| s.parse::<usize>().unwrap())
-        .collect();
+        .collect()
 }
 
 fn main() {
@@ -42,14 +41,7 @@ fn main() {
             adj[u].push(l);
             adj[v].push(r);
         }
-        let mut ok = true;
-        for i in 0..1 << k {
-            if adj[i].len() % 2 == 1 {
-                ok = false;
-                break;
-            }
-        }
-        if !ok {
+        if adj.iter().any(|list| list.len() % 2 == 1) {
             continue;
         }
         let mut st: Vec<usize> = vec![];
@@ -59,7 +51,7 @@ fn main() {
         vis[0] = true;
         vis[1] = true;
         while !st.is_empty() {
-            let curr = st.last().unwrap().clone();
+            let curr = *st.last().unwrap();
             let pair = curr ^ 1;
             let u = edges[curr];
             if adj[u].is_empty() {
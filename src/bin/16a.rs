@@ -7,24 +7,24 @@ fn main() {
   let n: usize = size[0].trim().parse().unwrap();
   let m: usize = size[1].trim().parse().unwrap();
   let mut flag = vec!["".to_owned(); n];
-  for i in 0..n {
-    io::stdin().read_line(&mut flag[i]).unwrap();
+  for line in flag.iter_mut() {
+    io::stdin().read_line(line).unwrap();
   }
   let mut last_line = flag[0].as_bytes()[0];
   let mut valid = true;
-  for i in 0..n {
-    let first_symbol = flag[i].as_bytes()[0];
+  for (i, line) in flag.iter().enumerate() {
+    let first_symbol = line.as_bytes()[0];
     if i > 0 && first_symbol == last_line {
       valid = false;
       break;
     }
     for j in 1..m {
-      if flag[i].as_bytes()[j] != first_symbol {
+      if line.as_bytes()[j] != first_symbol {
         valid = false;
         break;
       }
     }
-    last_line = flag[i].as_bytes()[0];
+    last_line = line.as_bytes()[0];
     if !valid {
       break;
     }
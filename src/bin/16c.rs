@@ -7,7 +7,7 @@ fn gcd(a: u64, b: u64) -> u64 {
   if b == 0 {
     return a;
   }
-  return gcd(b, a % b);
+  gcd(b, a % b)
 }
 
 fn main() {
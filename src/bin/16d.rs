@@ -10,8 +10,8 @@ fn main() {
   for _i in 0..n {
     let mut curr = String::new();
     io::stdin().read_line(&mut curr).unwrap();
-    let mut hour: i32 = (&curr[1..3]).parse().unwrap();
-    let mut minute: i32 = (&curr[4..6]).parse().unwrap();
+    let mut hour: i32 = curr[1..3].parse().unwrap();
+    let mut minute: i32 = curr[4..6].parse().unwrap();
     if &curr[7..8] == "p" && hour != 12 {
       hour += 12;
     }
@@ -0,0 +1,213 @@
+use std::{
+    error::Error,
+    fmt, io,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use serde::Deserialize;
+
+/// Cargo profile used for judging, see `[profile.judge]` in `Cargo.toml`.
+pub const PROFILE: &str = "judge";
+
+#[derive(Debug)]
+pub enum BuildError {
+    /// Cargo could not be run at all.
+    Io(io::Error),
+    /// Compilation failed; holds the rendered compiler errors.
+    Compile(String),
+    /// Cargo failed before compiling, e.g. because the bin does not exist.
+    Cargo(String),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuildError::Io(err) => write!(f, "failed to run cargo: {}", err),
+            BuildError::Compile(errors) => f.write_str(errors.trim_end()),
+            BuildError::Cargo(stderr) => f.write_str(stderr.trim_end()),
+        }
+    }
+}
+
+impl Error for BuildError {}
+
+impl From<io::Error> for BuildError {
+    fn from(err: io::Error) -> BuildError {
+        BuildError::Io(err)
+    }
+}
+
+#[derive(Deserialize)]
+struct Target {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Diagnostic {
+    level: String,
+    rendered: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+enum CargoOutput {
+    CompilerArtifact {
+        target: Target,
+        executable: Option<PathBuf>,
+        fresh: bool,
+    },
+    CompilerMessage {
+        message: Diagnostic,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// The executable of a bin and whether cargo found it up to date instead of recompiling.
+#[derive(Debug, PartialEq, Eq)]
+struct Artifact {
+    executable: PathBuf,
+    fresh: bool,
+}
+
+/// Compile `src/bin/<name>.rs` once and return the path of the produced executable.
+///
+/// Cargo decides whether the bin has to be recompiled; an unchanged bin is not rebuilt.
+pub fn build(name: &str) -> Result<PathBuf, BuildError> {
+    build_in(Path::new("."), name).map(|artifact| artifact.executable)
+}
+
+/// Build the bin `name` of the package in `dir`.
+fn build_in(dir: &Path, name: &str) -> Result<Artifact, BuildError> {
+    let output = Command::new("cargo")
+        .args(["build", "--profile", PROFILE, "--bin", name])
+        .arg("--message-format=json")
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .output()?;
+
+    let (artifact, errors) = parse_output(name, &String::from_utf8_lossy(&output.stdout));
+    match artifact {
+        Some(artifact) if output.status.success() => Ok(artifact),
+        _ if !errors.is_empty() => Err(BuildError::Compile(errors)),
+        _ => Err(BuildError::Cargo(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        )),
+    }
+}
+
+/// The artifact of the bin `name` and the rendered compiler errors in cargo's JSON messages.
+fn parse_output(name: &str, stdout: &str) -> (Option<Artifact>, String) {
+    let mut artifact = None;
+    let mut errors = String::new();
+    for line in stdout.lines() {
+        match serde_json::from_str(line) {
+            Ok(CargoOutput::CompilerArtifact {
+                target,
+                executable: Some(executable),
+                fresh,
+            }) if target.name == name => {
+                artifact = Some(Artifact { executable, fresh });
+            }
+            Ok(CargoOutput::CompilerMessage { message }) if message.level == "error" => {
+                errors.push_str(&message.rendered.unwrap_or_default());
+            }
+            _ => {}
+        }
+    }
+    (artifact, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_output() {
+        let stdout = [
+            r#"{"reason":"compiler-artifact","target":{"name":"cp_rust"},"executable":null,"fresh":true}"#,
+            r#"{"reason":"compiler-artifact","target":{"name":"other"},"executable":"/t/judge/other","fresh":false}"#,
+            r#"{"reason":"compiler-artifact","target":{"name":"a"},"executable":"/t/judge/a","fresh":false}"#,
+            r#"{"reason":"build-finished","success":true}"#,
+            "not json",
+        ]
+        .join("\n");
+        assert_eq!(
+            parse_output("a", &stdout),
+            (
+                Some(Artifact {
+                    executable: PathBuf::from("/t/judge/a"),
+                    fresh: false,
+                }),
+                String::new()
+            )
+        );
+        assert_eq!(parse_output("b", &stdout).0, None);
+
+        let stdout = [
+            r#"{"reason":"compiler-message","message":{"level":"warning","rendered":"warning: unused\n"}}"#,
+            r#"{"reason":"compiler-message","message":{"level":"error","rendered":"error: one\n"}}"#,
+            r#"{"reason":"compiler-message","message":{"level":"error","rendered":"error: two\n"}}"#,
+        ]
+        .join("\n");
+        assert_eq!(
+            parse_output("a", &stdout),
+            (None, "error: one\nerror: two\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_build() {
+        let dir = std::env::temp_dir().join(format!("cp-rust-build-{}", std::process::id()));
+        // Left over from an interrupted run, it would already be built.
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src/bin")).unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[package]\nname = \"scratch\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\n\
+             [workspace]\n\n[profile.judge]\ninherits = \"release\"\n",
+        )
+        .unwrap();
+        let source = dir.join("src/bin/a.rs");
+        fs::write(&source, "fn main() { println!(\"1\"); }\n").unwrap();
+
+        let first = build_in(&dir, "a").unwrap();
+        assert!(!first.fresh);
+        assert!(first
+            .executable
+            .starts_with(dir.canonicalize().unwrap().join("target").join(PROFILE)));
+        let output = Command::new(&first.executable).output().unwrap();
+        assert_eq!(output.stdout, b"1\n");
+
+        // Unchanged sources are not rebuilt, changed ones are.
+        assert_eq!(
+            build_in(&dir, "a").unwrap(),
+            Artifact {
+                executable: first.executable.clone(),
+                fresh: true,
+            }
+        );
+        fs::write(&source, "fn main() { println!(\"2\"); }\n").unwrap();
+        let second = build_in(&dir, "a").unwrap();
+        assert!(!second.fresh);
+        assert_eq!(
+            Command::new(&second.executable).output().unwrap().stdout,
+            b"2\n"
+        );
+
+        fs::write(&source, "fn main() { let x: u8 = \"\"; }\n").unwrap();
+        match build_in(&dir, "a") {
+            Err(BuildError::Compile(errors)) => assert!(errors.contains("mismatched types")),
+            other => panic!("expected a compile error, got {:?}", other),
+        }
+        assert!(matches!(
+            build_in(&dir, "missing"),
+            Err(BuildError::Cargo(_))
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
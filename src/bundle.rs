@@ -0,0 +1,535 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::lexer::{self, Kind, Token};
+use crate::prune;
+
+/// Name under which solutions use the library.
+pub const CRATE_NAME: &str = "cp_rust";
+
+/// The library modules in `src`, which solutions may use as `cp_rust::<module>`.
+pub struct Library {
+    dir: PathBuf,
+    /// Exported macros and the modules defining them.
+    macros: BTreeMap<String, String>,
+}
+
+impl Library {
+    /// Index the modules in `dir`, usually `src`.
+    pub fn open(dir: &Path) -> io::Result<Library> {
+        let mut macros = BTreeMap::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let Some(module) = module_name(&path) else {
+                continue;
+            };
+            for name in exported_macros(&fs::read_to_string(&path)?) {
+                macros.insert(name, module.clone());
+            }
+        }
+        Ok(Library {
+            dir: dir.to_path_buf(),
+            macros,
+        })
+    }
+
+    fn is_module(&self, name: &str) -> bool {
+        let path = self.dir.join(format!("{}.rs", name));
+        module_name(&path).is_some() && path.is_file()
+    }
+
+    fn is_macro(&self, name: &str) -> bool {
+        self.macros.contains_key(name)
+    }
+
+    /// The module a path starting with `name` needs: the module itself or the one defining
+    /// the macro.
+    fn resolve(&self, name: &str) -> io::Result<String> {
+        if self.is_module(name) {
+            Ok(name.to_string())
+        } else if let Some(module) = self.macros.get(name) {
+            Ok(module.clone())
+        } else {
+            Err(io::Error::other(format!(
+                "`{}::{}` is neither a module nor a macro of the library",
+                CRATE_NAME, name
+            )))
+        }
+    }
+}
+
+/// Module name of a library source file; the crate roots are not modules.
+fn module_name(path: &Path) -> Option<String> {
+    if path.extension()? != "rs" {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    match stem {
+        "lib" | "main" => None,
+        _ => Some(stem.to_string()),
+    }
+}
+
+/// Names of the `#[macro_export]` macros defined in `src`.
+fn exported_macros(src: &str) -> Vec<String> {
+    let tokens = code(&lexer::tokenize(src));
+    let mut macros = Vec::new();
+    let mut exported = false;
+    for (i, token) in tokens.iter().enumerate() {
+        if token.is_ident("macro_export") {
+            exported = true;
+        } else if token.is_punct(';') || token.is_punct('{') || token.is_punct('}') {
+            exported = false;
+        } else if token.is_ident("macro_rules") && exported {
+            if let Some(name) = tokens.get(i + 2).filter(|token| token.kind == Kind::Ident) {
+                macros.push(name.text.to_string());
+            }
+        }
+    }
+    macros
+}
+
+/// Tokens without whitespace and comments.
+fn code<'a>(tokens: &[Token<'a>]) -> Vec<Token<'a>> {
+    tokens
+        .iter()
+        .filter(|token| !token.is_trivia())
+        .copied()
+        .collect()
+}
+
+/// Maximum size of a Codeforces submission, in bytes.
+pub const SOURCE_LIMIT: usize = 64 << 10;
+
+/// What to leave out of the bundled library code.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Options {
+    /// Keep items the solution cannot reach.
+    pub keep_unused: bool,
+    pub strip_comments: bool,
+    /// Remove `#[cfg(test)]` items.
+    pub strip_tests: bool,
+}
+
+/// Build a self-contained source file from a solution using the library.
+///
+/// The library modules it needs, directly or through other modules, are appended inside
+/// `mod cp_rust` and paths to them are rewritten to `crate::cp_rust::...`. Exported macros
+/// stay at the crate root, where `#[macro_export]` puts them.
+pub fn bundle(solution: &str, library: &Library, options: &Options) -> io::Result<String> {
+    let (rewritten, mut pending) = rewrite_solution(solution, library)?;
+
+    let mut modules = BTreeMap::new();
+    while let Some(module) = pending.pop_first() {
+        if modules.contains_key(&module) {
+            continue;
+        }
+        let path = library.dir.join(format!("{}.rs", module));
+        let src = fs::read_to_string(&path)
+            .map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))?;
+        let (rewritten, dependencies) = rewrite_module(&src, library)?;
+        pending.extend(dependencies);
+        modules.insert(module, rewritten);
+    }
+
+    if modules.is_empty() {
+        return Ok(solution.to_string());
+    }
+    if options.strip_tests {
+        for src in modules.values_mut() {
+            *src = prune::strip_tests(src);
+        }
+    }
+    if !options.keep_unused {
+        prune::eliminate_dead_code(&rewritten, &mut modules);
+    }
+    if options.strip_comments {
+        for src in modules.values_mut() {
+            *src = prune::strip_comments(src);
+        }
+    }
+    let mut bundled = rewritten.trim_end().to_string();
+    bundled.push_str(&format!(
+        "\n\n// Bundled from the {} library.\n#[allow(dead_code, unused_imports)]\nmod {} {{\n",
+        CRATE_NAME, CRATE_NAME
+    ));
+    for (module, src) in modules {
+        bundled.push_str(&format!("pub mod {} {{\n{}\n}}\n", module, src.trim()));
+    }
+    bundled.push_str("}\n");
+    Ok(bundled)
+}
+
+/// One imported path of a use tree, e.g. `input::Scanner as S`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UseLeaf {
+    pub(crate) segments: Vec<String>,
+    pub(crate) alias: Option<String>,
+}
+
+/// Parse a use tree from code tokens, returning its leaves and the number of tokens read.
+pub(crate) fn parse_use_tree(tokens: &[Token]) -> (Vec<UseLeaf>, usize) {
+    let mut leaves = Vec::new();
+    let mut segments = Vec::new();
+    let mut i = 0;
+    loop {
+        match tokens.get(i) {
+            Some(token) if token.is_punct('{') => {
+                i += 1;
+                while tokens.get(i).is_some_and(|token| !token.is_punct('}')) {
+                    let (inner, read) = parse_use_tree(&tokens[i..]);
+                    leaves.extend(inner.into_iter().map(|mut leaf| {
+                        let mut path = segments.clone();
+                        path.append(&mut leaf.segments);
+                        leaf.segments = path;
+                        leaf
+                    }));
+                    i += read.max(1);
+                    if tokens.get(i).is_some_and(|token| token.is_punct(',')) {
+                        i += 1;
+                    }
+                }
+                return (leaves, i + 1);
+            }
+            Some(token) if token.kind == Kind::Ident || token.is_punct('*') => {
+                segments.push(token.text.to_string());
+                i += 1;
+            }
+            _ => break,
+        }
+        if tokens.get(i).is_some_and(|token| token.is_punct(':'))
+            && tokens.get(i + 1).is_some_and(|token| token.is_punct(':'))
+        {
+            i += 2;
+        } else {
+            break;
+        }
+    }
+    let mut alias = None;
+    if tokens.get(i).is_some_and(|token| token.is_ident("as")) {
+        alias = tokens.get(i + 1).map(|token| token.text.to_string());
+        i += 2;
+    }
+    leaves.push(UseLeaf { segments, alias });
+    (leaves, i)
+}
+
+/// Rewrite `cp_rust::` paths of a solution, returning the library modules it uses.
+fn rewrite_solution(src: &str, library: &Library) -> io::Result<(String, BTreeSet<String>)> {
+    let tokens = lexer::tokenize(src);
+    let code: Vec<(usize, Token)> = tokens
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(_, token)| !token.is_trivia())
+        .collect();
+    let code_tokens: Vec<Token> = code.iter().map(|&(_, token)| token).collect();
+    let is_path_sep = |k: usize| {
+        code_tokens.get(k).is_some_and(|t| t.is_punct(':'))
+            && code_tokens.get(k + 1).is_some_and(|t| t.is_punct(':'))
+    };
+
+    // Replacements of token ranges, by index into `tokens`.
+    let mut replacements: Vec<(usize, usize, String)> = Vec::new();
+    let mut modules = BTreeSet::new();
+    let mut depth = 0;
+    let mut k = 0;
+    while k < code.len() {
+        let token = code_tokens[k];
+        if token.is_punct('{') {
+            depth += 1;
+        } else if token.is_punct('}') {
+            depth -= 1;
+        } else if token.is_ident("use")
+            && code_tokens
+                .get(k + 1)
+                .is_some_and(|t| t.is_ident(CRATE_NAME))
+            && is_path_sep(k + 2)
+        {
+            let (leaves, read) = parse_use_tree(&code_tokens[k + 4..]);
+            let end = k + 4 + read;
+            if !code_tokens.get(end).is_some_and(|t| t.is_punct(';')) {
+                return Err(io::Error::other(format!(
+                    "failed to parse `use {}::...`",
+                    CRATE_NAME
+                )));
+            }
+            let mut imports = Vec::new();
+            for mut leaf in leaves {
+                // `input::{self}` imports the module itself.
+                if leaf.segments.len() > 1 && leaf.segments.last().is_some_and(|s| s == "self") {
+                    leaf.segments.pop();
+                }
+                let first = &leaf.segments[0];
+                if first == "*" {
+                    return Err(io::Error::other(format!(
+                        "glob imports from `{}` are not supported",
+                        CRATE_NAME
+                    )));
+                }
+                modules.insert(library.resolve(first)?);
+                let alias = leaf
+                    .alias
+                    .as_ref()
+                    .map_or(String::new(), |alias| format!(" as {}", alias));
+                if leaf.segments.len() > 1 || library.is_module(first) {
+                    // `use cp_rust::input;` may be there only for the `input!` macro.
+                    let allow = if leaf.segments.len() == 1 && library.is_macro(first) {
+                        "#[allow(unused_imports)] "
+                    } else {
+                        ""
+                    };
+                    imports.push(format!(
+                        "{}use crate::{}::{}{};",
+                        allow,
+                        CRATE_NAME,
+                        leaf.segments.join("::"),
+                        alias
+                    ));
+                }
+                // Exported macros already live at the crate root and cannot be imported there.
+                let renamed = leaf.alias.as_ref().is_some_and(|alias| alias != first);
+                if leaf.segments.len() == 1 && library.is_macro(first) && (depth > 0 || renamed) {
+                    imports.push(format!("use crate::{}{};", first, alias));
+                }
+            }
+            let (start, mut stop) = (code[k].0, code[end].0 + 1);
+            let mut text = imports.join(" ");
+            // Drop the line of a declaration that imports nothing any more.
+            if text.is_empty() {
+                if let Some(space) = tokens.get(stop).filter(|t| t.kind == Kind::Whitespace) {
+                    if let Some((_, rest)) = space.text.split_once('\n') {
+                        text = rest.to_string();
+                        stop += 1;
+                    }
+                }
+            }
+            replacements.push((start, stop, text));
+            k = end + 1;
+            continue;
+        } else if token.is_ident(CRATE_NAME)
+            && !(k >= 2 && is_path_sep(k - 2))
+            && is_path_sep(k + 1)
+        {
+            if let Some(name) = code_tokens.get(k + 3).filter(|t| t.kind == Kind::Ident) {
+                modules.insert(library.resolve(name.text)?);
+                let is_macro = code_tokens.get(k + 4).is_some_and(|t| t.is_punct('!'));
+                let path = if is_macro {
+                    "crate".to_string()
+                } else {
+                    format!("crate::{}", CRATE_NAME)
+                };
+                replacements.push((code[k].0, code[k].0 + 1, path));
+            }
+        }
+        k += 1;
+    }
+
+    Ok((apply(&tokens, replacements), modules))
+}
+
+/// Rewrite `crate::` paths of a library module to point into `mod cp_rust`, returning the
+/// modules it depends on.
+///
+/// Code under `#[cfg(test)]` is rewritten too, but its dependencies are ignored.
+fn rewrite_module(src: &str, library: &Library) -> io::Result<(String, BTreeSet<String>)> {
+    let tokens = lexer::tokenize(src);
+    let code: Vec<(usize, Token)> = tokens
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(_, token)| !token.is_trivia())
+        .collect();
+    let code_tokens: Vec<Token> = code.iter().map(|&(_, token)| token).collect();
+    let tests = prune::test_items(&code_tokens);
+
+    let mut replacements = Vec::new();
+    let mut modules = BTreeSet::new();
+    for k in 0..code.len() {
+        let is_crate_path = code_tokens[k].is_ident("crate")
+            && code_tokens.get(k + 1).is_some_and(|t| t.is_punct(':'))
+            && code_tokens.get(k + 2).is_some_and(|t| t.is_punct(':'));
+        if !is_crate_path {
+            continue;
+        }
+        let (leaves, _) = parse_use_tree(&code_tokens[k + 3..]);
+        let in_test = tests.iter().any(|range| range.contains(&k));
+        if !in_test {
+            for leaf in &leaves {
+                if let Some(first) = leaf.segments.first() {
+                    modules.insert(library.resolve(first)?);
+                }
+            }
+        }
+        // Macros stay at the crate root.
+        let is_macro = code_tokens.get(k + 4).is_some_and(|t| t.is_punct('!'));
+        if !is_macro {
+            let index = code[k].0;
+            replacements.push((index, index + 1, format!("crate::{}", CRATE_NAME)));
+        }
+    }
+
+    Ok((apply(&tokens, replacements), modules))
+}
+
+/// Concatenate tokens, replacing the ranges `(start, end, text)`.
+fn apply(tokens: &[Token], mut replacements: Vec<(usize, usize, String)>) -> String {
+    replacements.sort_by_key(|&(start, _, _)| start);
+    let mut out = String::new();
+    let mut i = 0;
+    for (start, end, text) in replacements {
+        out.extend(tokens[i..start].iter().map(|token| token.text));
+        out.push_str(&text);
+        i = end;
+    }
+    out.extend(tokens[i..].iter().map(|token| token.text));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    const INPUT: &str = r#"
+//! Reading input.
+use crate::util;
+
+pub fn words(s: &str) -> Vec<String> {
+    s.split_whitespace().map(util::own).collect()
+}
+
+#[macro_export]
+macro_rules! first_word {
+    ($s:expr) => {
+        $crate::input::words($s)[0].clone()
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::unbundled::Nothing;
+}
+"#;
+
+    const UTIL: &str = r#"
+pub fn own(s: &str) -> String {
+    s.to_string()
+}
+
+// Not used by `input`.
+pub fn shout(s: &str) -> String {
+    crate::util::own(s).to_uppercase()
+}
+
+pub fn unused() {}
+"#;
+
+    const SOLUTION: &str = r#"
+use cp_rust::input::{self, words as split};
+use cp_rust::first_word;
+
+fn main() {
+    let s = "crate::cp_rust hello world";
+    println!("{} {} {}", first_word!(s), split(s).len(), input::words(s)[2]);
+    println!("{}", cp_rust::util::shout("!"));
+    inner::run();
+}
+
+mod inner {
+    use cp_rust::first_word;
+
+    pub fn run() {
+        println!("{}", first_word!("inner"));
+    }
+}
+"#;
+
+    fn library(name: &str) -> (PathBuf, Library) {
+        let dir = std::env::temp_dir().join(format!("cp-rust-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("lib.rs"), "pub mod input;\npub mod util;\n").unwrap();
+        fs::write(dir.join("input.rs"), INPUT).unwrap();
+        fs::write(dir.join("util.rs"), UTIL).unwrap();
+        let library = Library::open(&dir).unwrap();
+        (dir, library)
+    }
+
+    #[test]
+    fn test_parse_use_tree() {
+        let tokens = code(&lexer::tokenize("{a::{b, c as d}, e::*};"));
+        let (leaves, read) = parse_use_tree(&tokens);
+        let leaf = |segments: &[&str], alias: Option<&str>| UseLeaf {
+            segments: segments.iter().map(|s| s.to_string()).collect(),
+            alias: alias.map(str::to_string),
+        };
+        assert_eq!(
+            leaves,
+            [
+                leaf(&["a", "b"], None),
+                leaf(&["a", "c"], Some("d")),
+                leaf(&["e", "*"], None)
+            ]
+        );
+        assert_eq!(read, tokens.len() - 1);
+    }
+
+    #[test]
+    fn test_bundle() {
+        let (dir, library) = library("bundle");
+        assert_eq!(
+            library.macros,
+            BTreeMap::from([("first_word".to_string(), "input".to_string())])
+        );
+
+        let bundled = bundle(SOLUTION, &library, &Options::default()).unwrap();
+        assert!(bundled.starts_with(
+            "\nuse crate::cp_rust::input; use crate::cp_rust::input::words as split;\n\nfn main()"
+        ));
+        assert!(bundled.contains("    use crate::first_word;\n"));
+        assert!(bundled.contains("$crate::cp_rust::input::words($s)"));
+        assert!(bundled.contains("pub mod util {"));
+        assert!(!bundled.contains("fn unused"));
+        assert!(!bundled.contains("unbundled"));
+
+        let options = Options {
+            keep_unused: true,
+            ..Options::default()
+        };
+        let kept = bundle(SOLUTION, &library, &options).unwrap();
+        assert!(kept.contains("fn unused"));
+
+        let source = dir.join("bundled.rs");
+        let binary = dir.join("bundled");
+        fs::write(&source, &bundled).unwrap();
+        let status = Command::new("rustc")
+            .args(["--edition", "2021", "-o"])
+            .arg(&binary)
+            .arg(&source)
+            .status()
+            .unwrap();
+        assert!(status.success(), "{}", bundled);
+        let output = Command::new(&binary).output().unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "crate::cp_rust 3 world\n!\ninner\n"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_bundle_errors() {
+        let (dir, library) = library("bundle-errors");
+        assert!(bundle("use cp_rust::*;", &library, &Options::default()).is_err());
+        assert!(bundle("use cp_rust::missing;", &library, &Options::default()).is_err());
+        assert_eq!(
+            bundle("fn main() {}", &library, &Options::default()).unwrap(),
+            "fn main() {}"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
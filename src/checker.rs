@@ -0,0 +1,268 @@
+use std::{
+    error::Error,
+    fmt, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{build, util::TempFile};
+
+/// Result of checking one output; `Err` explains why it was rejected.
+pub type CheckResult = Result<(), String>;
+
+/// Decides whether the output of a solution is acceptable for a test.
+pub trait Checker {
+    fn check(&self, input: &Path, expected: &str, actual: &str) -> io::Result<CheckResult>;
+}
+
+/// Byte-for-byte comparison.
+pub struct Exact;
+
+impl Checker for Exact {
+    fn check(&self, _input: &Path, expected: &str, actual: &str) -> io::Result<CheckResult> {
+        Ok(if expected == actual {
+            Ok(())
+        } else {
+            Err("output differs".to_string())
+        })
+    }
+}
+
+/// Line by line comparison, ignoring trailing whitespace and trailing blank lines.
+pub struct Lines;
+
+impl Checker for Lines {
+    fn check(&self, _input: &Path, expected: &str, actual: &str) -> io::Result<CheckResult> {
+        fn lines(s: &str) -> Vec<&str> {
+            let mut lines: Vec<&str> = s.lines().map(str::trim_end).collect();
+            while lines.last() == Some(&"") {
+                lines.pop();
+            }
+            lines
+        }
+
+        let (expected, actual) = (lines(expected), lines(actual));
+        for (i, (e, a)) in expected.iter().zip(&actual).enumerate() {
+            if e != a {
+                return Ok(Err(format!(
+                    "line {}: expected `{}`, found `{}`",
+                    i + 1,
+                    e,
+                    a
+                )));
+            }
+        }
+        Ok(compare_len("lines", expected.len(), actual.len()))
+    }
+}
+
+/// Whitespace-insensitive token comparison, like testlib's `wcmp`.
+pub struct Tokens {
+    pub ignore_case: bool,
+}
+
+impl Checker for Tokens {
+    fn check(&self, _input: &Path, expected: &str, actual: &str) -> io::Result<CheckResult> {
+        Ok(compare_tokens(expected, actual, |e, a| {
+            if self.ignore_case {
+                e.eq_ignore_ascii_case(a)
+            } else {
+                e == a
+            }
+        }))
+    }
+}
+
+/// Token comparison where numbers may differ by an absolute or relative error.
+pub struct Float {
+    pub abs: f64,
+    pub rel: f64,
+}
+
+impl Checker for Float {
+    fn check(&self, _input: &Path, expected: &str, actual: &str) -> io::Result<CheckResult> {
+        Ok(compare_tokens(expected, actual, |e, a| {
+            match (e.parse::<f64>(), a.parse::<f64>()) {
+                (Ok(e), Ok(a)) if e.is_finite() && a.is_finite() => {
+                    let diff = (e - a).abs();
+                    diff <= self.abs || diff <= self.rel * e.abs()
+                }
+                _ => e == a,
+            }
+        }))
+    }
+}
+
+/// A user-written checker, run as `<checker> <input> <expected> <actual>`.
+///
+/// Exit code zero accepts the output; anything the checker prints is reported on rejection.
+pub struct SpecialJudge {
+    pub binary: PathBuf,
+}
+
+impl Checker for SpecialJudge {
+    fn check(&self, input: &Path, expected: &str, actual: &str) -> io::Result<CheckResult> {
+        let expected_file = TempFile::new("ans", expected)?;
+        let actual_file = TempFile::new("out", actual)?;
+        let output = Command::new(&self.binary)
+            .arg(input)
+            .arg(&expected_file.0)
+            .arg(&actual_file.0)
+            .output()?;
+
+        Ok(if output.status.success() {
+            Ok(())
+        } else {
+            let mut message = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if message.is_empty() {
+                message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            }
+            if message.is_empty() {
+                message = format!("checker failed with {}", output.status);
+            }
+            Err(message)
+        })
+    }
+}
+
+/// A `--checker` argument that names no usable checker.
+#[derive(Debug)]
+pub enum SpecError {
+    /// The tolerance of `float:<eps>` is not a non-negative number.
+    Epsilon(String),
+    /// The special judge failed to build.
+    Build(build::BuildError),
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpecError::Epsilon(eps) => write!(
+                f,
+                "invalid tolerance `{}` for the float checker, expected e.g. float:1e-9",
+                eps
+            ),
+            SpecError::Build(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for SpecError {}
+
+impl From<build::BuildError> for SpecError {
+    fn from(err: build::BuildError) -> SpecError {
+        SpecError::Build(err)
+    }
+}
+
+/// Parse a `--checker` argument.
+///
+/// Built-in checkers are `exact`, `lines`, `tokens`, `icase` (case-insensitive tokens) and
+/// `float[:EPS]`; any other name is built from `src/bin/<name>.rs` as a special judge.
+pub fn from_spec(spec: &str) -> Result<Box<dyn Checker>, SpecError> {
+    let checker: Box<dyn Checker> = match spec {
+        "exact" => Box::new(Exact),
+        "lines" => Box::new(Lines),
+        "tokens" => Box::new(Tokens { ignore_case: false }),
+        "icase" => Box::new(Tokens { ignore_case: true }),
+        "float" => Box::new(Float {
+            abs: 1e-6,
+            rel: 1e-6,
+        }),
+        _ => match spec.strip_prefix("float:") {
+            Some(eps) => match eps.parse::<f64>() {
+                Ok(eps) if eps >= 0.0 && eps.is_finite() => Box::new(Float { abs: eps, rel: eps }),
+                _ => return Err(SpecError::Epsilon(eps.to_string())),
+            },
+            None => Box::new(SpecialJudge {
+                binary: build::build(spec)?,
+            }),
+        },
+    };
+    Ok(checker)
+}
+
+fn compare_tokens(expected: &str, actual: &str, eq: impl Fn(&str, &str) -> bool) -> CheckResult {
+    let expected: Vec<&str> = expected.split_whitespace().collect();
+    let actual: Vec<&str> = actual.split_whitespace().collect();
+    for (i, (e, a)) in expected.iter().zip(&actual).enumerate() {
+        if !eq(e, a) {
+            return Err(format!("token {}: expected `{}`, found `{}`", i + 1, e, a));
+        }
+    }
+    compare_len("tokens", expected.len(), actual.len())
+}
+
+fn compare_len(what: &str, expected: usize, actual: usize) -> CheckResult {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(format!("expected {} {}, found {}", expected, what, actual))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use crate::test_util::script;
+
+    fn check(checker: &dyn Checker, expected: &str, actual: &str) -> CheckResult {
+        checker.check(Path::new(""), expected, actual).unwrap()
+    }
+
+    #[test]
+    fn test_lines() {
+        assert!(check(&Lines, "1 2\n3\n", "1 2   \n3").is_ok());
+        assert!(check(&Lines, "YES\n", "YES\n\n\n").is_ok());
+        assert!(check(&Lines, "1 2\n3\n", "1 2 3\n").is_err());
+        assert!(check(&Lines, "YES\n", "yes\n").is_err());
+    }
+
+    #[test]
+    fn test_tokens() {
+        let tokens = Tokens { ignore_case: false };
+        assert!(check(&tokens, "1 2\n3\n", "1 2 3").is_ok());
+        assert_eq!(
+            check(&tokens, "1 2 3", "1 4 3"),
+            Err("token 2: expected `2`, found `4`".to_string())
+        );
+        assert_eq!(
+            check(&tokens, "1 2", "1 2 3"),
+            Err("expected 2 tokens, found 3".to_string())
+        );
+        assert!(check(&tokens, "YES", "yes").is_err());
+        assert!(check(&Tokens { ignore_case: true }, "YES", "yes").is_ok());
+    }
+
+    #[test]
+    fn test_float() {
+        let float = Float {
+            abs: 1e-6,
+            rel: 1e-6,
+        };
+        assert!(check(&float, "0.5 1000000", "0.5000001 1000000.9").is_ok());
+        assert!(check(&float, "0.5", "0.501").is_err());
+        assert!(check(&float, "0.5 NO", "0.5 YES").is_err());
+        assert!(check(&float, "nan", "nan").is_ok());
+    }
+
+    #[test]
+    fn test_float_spec() {
+        assert!(from_spec("float:1e-9").is_ok());
+        for spec in ["float:abc", "float:", "float:-1", "float:inf"] {
+            assert!(matches!(from_spec(spec), Err(SpecError::Epsilon(_))));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_special_judge() {
+        let script = script("cmp -s \"$2\" \"$3\" || echo differ\n[ \"$(cat \"$3\")\" = ok ]");
+        let judge = SpecialJudge {
+            binary: script.0.clone(),
+        };
+        assert!(check(&judge, "ok", "ok").is_ok());
+        assert_eq!(check(&judge, "ok", "ko"), Err("differ".to_string()));
+    }
+}
@@ -2,14 +2,13 @@ use std::{
     collections::{BTreeMap, HashMap},
     env,
     error::Error,
-    fs, time,
+    time,
 };
 
 use data_encoding::HEXLOWER;
 use fantoccini::Locator;
 use rand::distributions::Alphanumeric;
 use rand::prelude::*;
-use reqwest;
 use ring::digest::{Context, SHA512};
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -44,11 +43,11 @@ struct User {
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct Problem {
-    contest_id: Option<u32>,
-    problemset_name: Option<String>,
-    index: String,
-    name: String,
+pub struct Problem {
+    pub contest_id: Option<u32>,
+    pub problemset_name: Option<String>,
+    pub index: String,
+    pub name: String,
     r#type: String,
     points: Option<f64>,
     rating: Option<u32>,
@@ -71,6 +70,25 @@ struct ProblemQueryResult {
     problem_statistics: Vec<ProblemStatistics>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Contest {
+    id: u32,
+    name: String,
+    r#type: String,
+    phase: String,
+    frozen: bool,
+    duration_seconds: u32,
+    start_time_seconds: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Standings {
+    contest: Contest,
+    problems: Vec<Problem>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct BlogEntry {
@@ -115,7 +133,11 @@ impl Session {
         Ok(())
     }
 
-    pub async fn submit(&mut self, problem: String) -> Result<(), fantoccini::error::CmdError> {
+    pub async fn submit(
+        &mut self,
+        problem: String,
+        code: &str,
+    ) -> Result<(), fantoccini::error::CmdError> {
         if let Some(ref mut c) = self.client {
             c.goto("https://codeforces.com/problemset/submit").await?;
 
@@ -127,10 +149,8 @@ impl Session {
             lang.select_by_value(&CODEFORCES_RUST_LANG_ID.to_string())
                 .await?;
 
-            let filename = format!("src/bin/{}.rs", problem);
-            let code = fs::read_to_string(filename).expect("failed to read from file");
             let code_area = c.find(Locator::Css(".ace_text-input")).await?;
-            code_area.send_keys(&code).await?;
+            code_area.send_keys(code).await?;
 
             f.submit().await?;
 
@@ -171,6 +191,24 @@ pub async fn get_problemset_problems(
     Ok(())
 }
 
+/// Problems of a contest, in order, taken from the first row of its standings.
+pub async fn get_contest_problems(contest_id: u32) -> Result<Vec<Problem>, Box<dyn Error>> {
+    let resp: CodeforcesResponse<Standings> = reqwest::get(&format!(
+        "https://codeforces.com/api/contest.standings?contestId={}&from=1&count=1",
+        contest_id
+    ))
+    .await?
+    .json()
+    .await?;
+    match resp.result {
+        Some(standings) => Ok(standings.problems),
+        None => Err(resp
+            .comment
+            .unwrap_or_else(|| format!("contest.standings returned {}", resp.status))
+            .into()),
+    }
+}
+
 pub async fn get_user_info(handles: &str) -> Result<(), Box<dyn Error>> {
     let resp: CodeforcesResponse<Vec<User>> = reqwest::get(&format!(
         "https://codeforces.com/api/user.info?handles={}",
@@ -195,6 +233,39 @@ pub async fn get_blog_entry(number: u32) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Split a problem id such as `1361c` or `1472f2` into its contest id and uppercase index.
+pub fn parse_problem_id(id: &str) -> Option<(u32, String)> {
+    let digits = id.find(|c: char| !c.is_ascii_digit())?;
+    let contest_id = id[..digits].parse().ok()?;
+    let index = &id[digits..];
+    if !index.starts_with(|c: char| c.is_ascii_alphabetic())
+        || !index.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        return None;
+    }
+    Some((contest_id, index.to_uppercase()))
+}
+
+/// URL of a problem statement; contest ids from 100000 on belong to the gym.
+pub fn problem_url(contest_id: u32, index: &str) -> String {
+    let section = if contest_id >= 100000 {
+        "gym"
+    } else {
+        "contest"
+    };
+    format!(
+        "https://codeforces.com/{}/{}/problem/{}",
+        section, contest_id, index
+    )
+}
+
+pub async fn get_problem_page(contest_id: u32, index: &str) -> Result<String, Box<dyn Error>> {
+    let resp = reqwest::get(&problem_url(contest_id, index))
+        .await?
+        .error_for_status()?;
+    Ok(resp.text().await?)
+}
+
 pub fn sign(method: String, params: HashMap<String, String>) -> String {
     let cf_key = env::var("CODEFORCES_API_KEY").unwrap();
     let cf_secret = env::var("CODEFORCES_API_SECRET").unwrap();
@@ -234,6 +305,16 @@ mod tests {
     use super::*;
     use dotenv::dotenv;
 
+    #[test]
+    fn test_parse_problem_id() {
+        assert_eq!(parse_problem_id("1361c"), Some((1361, "C".to_string())));
+        assert_eq!(parse_problem_id("1472f2"), Some((1472, "F2".to_string())));
+        assert_eq!(parse_problem_id("4a"), Some((4, "A".to_string())));
+        assert_eq!(parse_problem_id("1361"), None);
+        assert_eq!(parse_problem_id("brute"), None);
+        assert_eq!(parse_problem_id("16c-gen"), None);
+    }
+
     #[test]
     fn test_sign() {
         dotenv().ok();
@@ -0,0 +1,188 @@
+//! Counting modulo a prime.
+use crate::modint::{ModInt, Modulus};
+
+/// Factorials and inverse factorials up to `n`, and the counts built from them.
+///
+/// The modulus must be a prime greater than `n`; [`Combinatorics::lucas`] lifts the limit for
+/// binomials when the table covers a small prime modulus entirely.
+pub struct Combinatorics<M: Modulus> {
+    fact: Vec<ModInt<M>>,
+    inv_fact: Vec<ModInt<M>>,
+}
+
+impl<M: Modulus> Combinatorics<M> {
+    pub fn new(n: usize) -> Self {
+        let mut fact = vec![ModInt::new(1); n + 1];
+        for i in 1..=n {
+            fact[i] = fact[i - 1] * i;
+        }
+        let mut inv_fact = vec![ModInt::new(1); n + 1];
+        inv_fact[n] = fact[n].inv();
+        for i in (1..=n).rev() {
+            inv_fact[i - 1] = inv_fact[i] * i;
+        }
+        Combinatorics { fact, inv_fact }
+    }
+
+    /// Largest `n` the tables cover.
+    pub fn limit(&self) -> usize {
+        self.fact.len() - 1
+    }
+
+    pub fn fact(&self, n: usize) -> ModInt<M> {
+        self.fact[n]
+    }
+
+    pub fn inv_fact(&self, n: usize) -> ModInt<M> {
+        self.inv_fact[n]
+    }
+
+    /// Inverse of `1 <= n <= limit()`.
+    pub fn inv(&self, n: usize) -> ModInt<M> {
+        assert!(n >= 1, "0 has no inverse");
+        self.inv_fact[n] * self.fact[n - 1]
+    }
+
+    /// Ways to choose `k` of `n` items; 0 if `k > n`.
+    pub fn binom(&self, n: usize, k: usize) -> ModInt<M> {
+        if k > n {
+            return ModInt::new(0);
+        }
+        self.fact[n] * self.inv_fact[k] * self.inv_fact[n - k]
+    }
+
+    /// Ways to arrange `k` of `n` items in order; 0 if `k > n`.
+    pub fn perm(&self, n: usize, k: usize) -> ModInt<M> {
+        if k > n {
+            return ModInt::new(0);
+        }
+        self.fact[n] * self.inv_fact[n - k]
+    }
+
+    /// Ways to split `sum(ks)` items into groups of sizes `ks`.
+    pub fn multinomial(&self, ks: &[usize]) -> ModInt<M> {
+        let n: usize = ks.iter().sum();
+        ks.iter()
+            .fold(self.fact[n], |acc, &k| acc * self.inv_fact[k])
+    }
+
+    /// The `n`-th Catalan number, `binom(2n, n) / (n + 1)`; needs `2n <= limit()`.
+    pub fn catalan(&self, n: usize) -> ModInt<M> {
+        self.fact[2 * n] * self.inv_fact[n] * self.inv_fact[n + 1]
+    }
+
+    /// Stirling number of the second kind: ways to partition `n` items into `k` nonempty
+    /// sets, in `O(k log n)`.
+    pub fn stirling2(&self, n: usize, k: usize) -> ModInt<M> {
+        // Inclusion–exclusion over the sets left empty.
+        let sum: ModInt<M> = (0..=k)
+            .map(|i| {
+                let term = self.binom(k, i) * ModInt::new(k - i).pow(n as u64);
+                if i % 2 == 0 {
+                    term
+                } else {
+                    -term
+                }
+            })
+            .sum();
+        sum * self.inv_fact[k]
+    }
+
+    /// `binom(n, k)` for arbitrarily large `n` by Lucas' theorem; the table must cover the
+    /// whole prime modulus, i.e. `limit() >= modulus - 1`.
+    pub fn lucas(&self, mut n: u64, mut k: u64) -> ModInt<M> {
+        let p = u64::from(M::modulus());
+        assert!(
+            self.limit() as u64 >= p - 1,
+            "the table must cover the modulus"
+        );
+        let mut result = ModInt::new(1);
+        while k > 0 {
+            result *= self.binom((n % p) as usize, (k % p) as usize);
+            n /= p;
+            k /= p;
+        }
+        result
+    }
+}
+
+/// Unsigned Stirling numbers of the first kind `c(i, j)` for `i, j <= n`: permutations of `i`
+/// items with `j` cycles.
+pub fn stirling1_table<M: Modulus>(n: usize) -> Vec<Vec<ModInt<M>>> {
+    let mut table = vec![vec![ModInt::new(0); n + 1]; n + 1];
+    table[0][0] = ModInt::new(1);
+    for i in 1..=n {
+        for j in 1..=i {
+            table[i][j] = table[i - 1][j - 1] + table[i - 1][j] * (i - 1);
+        }
+    }
+    table
+}
+
+/// Stirling numbers of the second kind `S(i, j)` for `i, j <= n`: partitions of `i` items
+/// into `j` nonempty sets.
+pub fn stirling2_table<M: Modulus>(n: usize) -> Vec<Vec<ModInt<M>>> {
+    let mut table = vec![vec![ModInt::new(0); n + 1]; n + 1];
+    table[0][0] = ModInt::new(1);
+    for i in 1..=n {
+        for j in 1..=i {
+            table[i][j] = table[i - 1][j - 1] + table[i - 1][j] * j;
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modint::{ConstModulus, ModInt998244353, StaticModInt};
+
+    type Mint = ModInt998244353;
+
+    #[test]
+    fn test_binomials() {
+        let c = Combinatorics::<ConstModulus<998244353>>::new(20);
+        assert_eq!(c.limit(), 20);
+        assert_eq!(c.fact(10), Mint::new(3628800));
+        assert_eq!(c.fact(5) * c.inv_fact(5), Mint::new(1));
+        assert_eq!(c.inv(7) * 7, Mint::new(1));
+        assert_eq!(c.binom(10, 3), Mint::new(120));
+        assert_eq!(c.binom(3, 10), Mint::new(0));
+        assert_eq!(c.binom(0, 0), Mint::new(1));
+        assert_eq!(c.perm(10, 3), Mint::new(720));
+        assert_eq!(c.multinomial(&[2, 1, 1]), Mint::new(12));
+        assert_eq!(
+            (0..6).map(|n| c.catalan(n).val()).collect::<Vec<_>>(),
+            [1, 1, 2, 5, 14, 42]
+        );
+    }
+
+    #[test]
+    fn test_stirling() {
+        let c = Combinatorics::<ConstModulus<998244353>>::new(10);
+        let first = stirling1_table::<ConstModulus<998244353>>(5);
+        let second = stirling2_table::<ConstModulus<998244353>>(5);
+        assert_eq!(
+            first[5].iter().map(|x| x.val()).collect::<Vec<_>>(),
+            [0, 24, 50, 35, 10, 1]
+        );
+        assert_eq!(
+            second[5].iter().map(|x| x.val()).collect::<Vec<_>>(),
+            [0, 1, 15, 25, 10, 1]
+        );
+        for (i, row) in second.iter().enumerate() {
+            for (j, &s) in row.iter().enumerate() {
+                assert_eq!(c.stirling2(i, j), s, "S({}, {})", i, j);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lucas() {
+        let c = Combinatorics::<ConstModulus<7>>::new(6);
+        // binom(1000, 300) = 1 (mod 7), by Lucas: 1000 = 2626_7, 300 = 0606_7.
+        assert_eq!(c.lucas(1000, 300), StaticModInt::<7>::new(1));
+        assert_eq!(c.lucas(10, 3), StaticModInt::<7>::new(120));
+        assert_eq!(c.lucas(6, 7), StaticModInt::<7>::new(0));
+    }
+}
@@ -0,0 +1,500 @@
+use std::{fmt, ops::Range};
+
+/// Above this many cells the line alignment falls back to pairing lines by position.
+const ALIGN_LIMIT: usize = 4_000_000;
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const HIGHLIGHT: &str = "\x1b[1;4m";
+const RESET: &str = "\x1b[0m";
+
+/// How a diff is laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffOptions {
+    /// Show expected and actual output in two columns instead of one below the other.
+    pub side_by_side: bool,
+    /// Maximum width of a rendered line; longer lines are cut around the first difference.
+    pub width: usize,
+    /// Unchanged lines shown around each change.
+    pub context: usize,
+    /// Maximum number of rendered rows.
+    pub max_rows: usize,
+    /// Use ANSI colors and highlight the first differing token of each changed line.
+    pub color: bool,
+}
+
+impl Default for DiffOptions {
+    fn default() -> DiffOptions {
+        DiffOptions {
+            side_by_side: false,
+            width: 100,
+            context: 2,
+            max_rows: 40,
+            color: false,
+        }
+    }
+}
+
+/// Where two outputs first differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    /// 1-based line number in the expected output.
+    pub line: usize,
+    /// 1-based column of the differing token, in characters.
+    pub column: usize,
+    /// 1-based index of the differing token within the line.
+    pub token: usize,
+    /// The expected token, or `None` past the end of the line or output.
+    pub expected: Option<String>,
+    /// The actual token, or `None` past the end of the line or output.
+    pub actual: Option<String>,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn token(token: &Option<String>) -> String {
+            token
+                .as_ref()
+                .map_or("nothing".to_string(), |token| format!("`{}`", token))
+        }
+        write!(
+            f,
+            "line {}, column {} (token {}): expected {}, found {}",
+            self.line,
+            self.column,
+            self.token,
+            token(&self.expected),
+            token(&self.actual)
+        )
+    }
+}
+
+/// One row of an aligned diff: indices into the expected and actual lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Equal(usize, usize),
+    Change(Option<usize>, Option<usize>),
+}
+
+/// Output split into lines, ignoring trailing whitespace and trailing blank lines.
+fn lines(s: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = s.lines().map(str::trim_end).collect();
+    while lines.last() == Some(&"") {
+        lines.pop();
+    }
+    lines
+}
+
+/// Align lines with a longest common subsequence, pairing up removed and added lines.
+fn align(expected: &[&str], actual: &[&str]) -> Vec<Row> {
+    let prefix = expected
+        .iter()
+        .zip(actual)
+        .take_while(|(e, a)| e == a)
+        .count();
+    let suffix = expected[prefix..]
+        .iter()
+        .rev()
+        .zip(actual[prefix..].iter().rev())
+        .take_while(|(e, a)| e == a)
+        .count();
+    let e = &expected[prefix..expected.len() - suffix];
+    let a = &actual[prefix..actual.len() - suffix];
+
+    // Each edit is `(Some(i), None)` for a removed line or `(None, Some(j))` for an added one.
+    let mut edits = Vec::new();
+    if (e.len() + 1) * (a.len() + 1) <= ALIGN_LIMIT {
+        let width = a.len() + 1;
+        let mut lcs = vec![0u32; (e.len() + 1) * width];
+        for i in (0..e.len()).rev() {
+            for j in (0..a.len()).rev() {
+                lcs[i * width + j] = if e[i] == a[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < e.len() || j < a.len() {
+            if i < e.len() && j < a.len() && e[i] == a[j] {
+                edits.push((Some(i), Some(j)));
+                i += 1;
+                j += 1;
+            } else if j == a.len()
+                || (i < e.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+            {
+                edits.push((Some(i), None));
+                i += 1;
+            } else {
+                edits.push((None, Some(j)));
+                j += 1;
+            }
+        }
+    } else {
+        for k in 0..e.len().max(a.len()) {
+            let (i, j) = ((k < e.len()).then_some(k), (k < a.len()).then_some(k));
+            if i.is_some() && j.is_some() && e[k] == a[k] {
+                edits.push((i, j));
+            } else {
+                edits.push((i, None));
+                edits.push((None, j));
+            }
+        }
+    }
+
+    let mut rows: Vec<Row> = (0..prefix).map(|i| Row::Equal(i, i)).collect();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    let flush = |rows: &mut Vec<Row>, removed: &mut Vec<usize>, added: &mut Vec<usize>| {
+        for k in 0..removed.len().max(added.len()) {
+            rows.push(Row::Change(removed.get(k).copied(), added.get(k).copied()));
+        }
+        removed.clear();
+        added.clear();
+    };
+    for edit in edits {
+        match edit {
+            (Some(i), Some(j)) => {
+                flush(&mut rows, &mut removed, &mut added);
+                rows.push(Row::Equal(prefix + i, prefix + j));
+            }
+            (Some(i), None) => removed.push(prefix + i),
+            (None, Some(j)) => added.push(prefix + j),
+            (None, None) => {}
+        }
+    }
+    flush(&mut rows, &mut removed, &mut added);
+    let (e_end, a_end) = (expected.len() - suffix, actual.len() - suffix);
+    rows.extend((0..suffix).map(|k| Row::Equal(e_end + k, a_end + k)));
+    rows
+}
+
+/// Whitespace-separated tokens of a line, as character ranges.
+fn tokens(line: &str) -> Vec<Range<usize>> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in line.chars().chain(Some(' ')).enumerate() {
+        match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                tokens.push(s..i);
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    tokens
+}
+
+fn slice(line: &str, range: Range<usize>) -> String {
+    line.chars()
+        .skip(range.start)
+        .take(range.end - range.start)
+        .collect()
+}
+
+/// The first differing token of two lines: its index and its range in each line.
+///
+/// A missing token is an empty range at the end of the line.
+fn token_diff(expected: &str, actual: &str) -> Option<(usize, Range<usize>, Range<usize>)> {
+    let (e, a) = (tokens(expected), tokens(actual));
+    let end = |line: &str| {
+        let len = line.chars().count();
+        len..len
+    };
+    (0..e.len().max(a.len())).find_map(|k| {
+        let e_range = e.get(k).cloned().unwrap_or_else(|| end(expected));
+        let a_range = a.get(k).cloned().unwrap_or_else(|| end(actual));
+        if slice(expected, e_range.clone()) == slice(actual, a_range.clone()) {
+            None
+        } else {
+            Some((k, e_range, a_range))
+        }
+    })
+}
+
+/// Find the first differing token, comparing line by line.
+pub fn first_difference(expected: &str, actual: &str) -> Option<Difference> {
+    let (e, a) = (lines(expected), lines(actual));
+    let token = |line: &str, range: Range<usize>| {
+        if range.is_empty() {
+            None
+        } else {
+            Some(slice(line, range))
+        }
+    };
+    for k in 0..e.len().max(a.len()) {
+        let (e_line, a_line) = (e.get(k).copied(), a.get(k).copied());
+        if e_line == a_line {
+            continue;
+        }
+        let (e_line, a_line) = (e_line.unwrap_or(""), a_line.unwrap_or(""));
+        return Some(match token_diff(e_line, a_line) {
+            Some((index, e_range, a_range)) => Difference {
+                line: k + 1,
+                column: e_range.start + 1,
+                token: index + 1,
+                expected: token(e_line, e_range),
+                actual: token(a_line, a_range),
+            },
+            // Same tokens, different spacing.
+            None => Difference {
+                line: k + 1,
+                column: 1,
+                token: 1,
+                expected: Some(e_line.to_string()),
+                actual: Some(a_line.to_string()),
+            },
+        });
+    }
+    None
+}
+
+/// Cut `line` to `width` characters around `focus`, marking cuts with `…`, and optionally
+/// wrap `highlight` in `color`.
+fn render_line(
+    line: &str,
+    width: usize,
+    focus: usize,
+    highlight: Option<(Range<usize>, &str)>,
+) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut window = 0..chars.len();
+    if chars.len() > width {
+        let start = focus.saturating_sub(width / 3).min(chars.len() - width);
+        window = start..start + width;
+    }
+
+    let mut rendered = String::new();
+    if window.start > 0 {
+        rendered.push('…');
+    }
+    for i in window.clone() {
+        if let Some((ref range, color)) = highlight {
+            if i == range.start {
+                rendered.push_str(color);
+                rendered.push_str(HIGHLIGHT);
+            }
+        }
+        rendered.push(chars[i]);
+        if let Some((ref range, _)) = highlight {
+            if i + 1 == range.end || i + 1 == window.end && range.contains(&i) {
+                rendered.push_str(RESET);
+            }
+        }
+    }
+    if window.end < chars.len() {
+        rendered.push('…');
+    }
+    rendered
+}
+
+/// Pad a rendered cell to `width` visible characters, ignoring ANSI escapes.
+fn pad(cell: &str, width: usize) -> String {
+    let mut visible = 0;
+    let mut escape = false;
+    for c in cell.chars() {
+        match c {
+            '\x1b' => escape = true,
+            'm' if escape => escape = false,
+            _ if !escape => visible += 1,
+            _ => {}
+        }
+    }
+    format!("{}{}", cell, " ".repeat(width.saturating_sub(visible)))
+}
+
+/// Render a diff of `expected` against `actual`; empty if they match line by line.
+pub fn render(expected: &str, actual: &str, options: &DiffOptions) -> String {
+    let (e, a) = (lines(expected), lines(actual));
+    let rows = align(&e, &a);
+
+    // Rows worth showing: every change and its context.
+    let mut shown = vec![false; rows.len()];
+    for (k, row) in rows.iter().enumerate() {
+        if let Row::Change(..) = row {
+            let start = k.saturating_sub(options.context);
+            let end = (k + options.context + 1).min(rows.len());
+            shown[start..end].iter_mut().for_each(|shown| *shown = true);
+        }
+    }
+    if !shown.contains(&true) {
+        return String::new();
+    }
+
+    let number_width = e.len().max(a.len()).to_string().len();
+    let cell_width = if options.side_by_side {
+        options.width.saturating_sub(2 * number_width + 5) / 2
+    } else {
+        options.width
+    }
+    .max(8);
+    let color = |color: &'static str| if options.color { color } else { "" };
+    let reset = if options.color { RESET } else { "" };
+
+    let mut out = Vec::new();
+    let mut skipped = false;
+    for (k, row) in rows.iter().enumerate() {
+        if !shown[k] {
+            skipped = true;
+            continue;
+        }
+        if skipped || (k > 0 && out.is_empty()) {
+            out.push("...".to_string());
+            skipped = false;
+        }
+
+        let (e_line, a_line) = match *row {
+            Row::Equal(i, j) => (Some(i), Some(j)),
+            Row::Change(i, j) => (i, j),
+        };
+        let diff = match (*row, e_line, a_line) {
+            (Row::Change(..), Some(i), Some(j)) => token_diff(e[i], a[j]),
+            _ => None,
+        };
+        let focus = diff.as_ref().map_or(0, |(_, e_range, _)| e_range.start);
+        let cell = |line: &str, range: Option<Range<usize>>, highlight: &'static str| {
+            let highlight = range
+                .filter(|_| options.color)
+                .map(|range| (range, highlight));
+            render_line(line, cell_width, focus, highlight)
+        };
+        let e_cell = e_line.map(|i| cell(e[i], diff.as_ref().map(|d| d.1.clone()), RED));
+        let a_cell = a_line.map(|j| cell(a[j], diff.as_ref().map(|d| d.2.clone()), GREEN));
+        let number = |index: Option<usize>| {
+            index.map_or(" ".repeat(number_width), |index| {
+                format!("{:>width$}", index + 1, width = number_width)
+            })
+        };
+
+        if options.side_by_side {
+            let separator = match (*row, e_line, a_line) {
+                (Row::Equal(..), _, _) => ' ',
+                (_, Some(_), Some(_)) => '|',
+                (_, Some(_), None) => '<',
+                _ => '>',
+            };
+            out.push(format!(
+                "{} {} {} {} {}",
+                number(e_line),
+                pad(e_cell.as_deref().unwrap_or(""), cell_width),
+                separator,
+                number(a_line),
+                a_cell.unwrap_or_default()
+            ));
+        } else if let Row::Equal(..) = row {
+            out.push(format!(
+                "  {} {}",
+                number(e_line),
+                e_cell.unwrap_or_default()
+            ));
+        } else {
+            if let Some(e_cell) = e_cell {
+                out.push(format!(
+                    "{}- {} {}{}",
+                    color(RED),
+                    number(e_line),
+                    e_cell,
+                    reset
+                ));
+            }
+            if let Some(a_cell) = a_cell {
+                out.push(format!(
+                    "{}+ {} {}{}",
+                    color(GREEN),
+                    number(a_line),
+                    a_cell,
+                    reset
+                ));
+            }
+        }
+    }
+    if skipped {
+        out.push("...".to_string());
+    }
+
+    if out.len() > options.max_rows {
+        let hidden = out.len() - options.max_rows;
+        out.truncate(options.max_rows);
+        out.push(format!("... {} more rows", hidden));
+    }
+    let mut rendered = out.join("\n");
+    rendered.push('\n');
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_difference() {
+        assert_eq!(first_difference("1 2\n3\n", "1 2  \n3\n\n"), None);
+        assert_eq!(
+            first_difference("1\n2 3 4\n", "1\n2 33 4\n"),
+            Some(Difference {
+                line: 2,
+                column: 3,
+                token: 2,
+                expected: Some("3".to_string()),
+                actual: Some("33".to_string()),
+            })
+        );
+        let difference = first_difference("1 2\n", "1\n").unwrap();
+        assert_eq!(
+            difference.to_string(),
+            "line 1, column 3 (token 2): expected `2`, found nothing"
+        );
+    }
+
+    #[test]
+    fn test_align() {
+        let rows = align(&["a", "b", "c", "d"], &["a", "x", "c", "d", "e"]);
+        assert_eq!(
+            rows,
+            [
+                Row::Equal(0, 0),
+                Row::Change(Some(1), Some(1)),
+                Row::Equal(2, 2),
+                Row::Equal(3, 3),
+                Row::Change(None, Some(4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let options = DiffOptions {
+            context: 1,
+            ..DiffOptions::default()
+        };
+        assert_eq!(render("1\n2\n", "1\n2\n", &options), "");
+        assert_eq!(
+            render("1\n2\n3\n4\n5\n", "1\n2\n3\n4\n6\n", &options),
+            "...\n  4 4\n- 5 5\n+ 5 6\n"
+        );
+
+        let options = DiffOptions {
+            side_by_side: true,
+            width: 20,
+            ..options
+        };
+        assert_eq!(
+            render("a\nb\n", "a\nc\nd\n", &options),
+            "1 a          1 a\n2 b        | 2 c\n           > 3 d\n"
+        );
+    }
+
+    #[test]
+    fn test_render_line() {
+        let line = "0123456789".repeat(3);
+        assert_eq!(render_line(&line, 30, 0, None), line);
+        assert_eq!(render_line(&line, 10, 0, None), "0123456789…");
+        assert_eq!(render_line(&line, 10, 15, None), "…2345678901…");
+        assert_eq!(
+            render_line("1 22 3", 10, 2, Some((2..4, RED))),
+            format!("1 {}{}22{} 3", RED, HIGHLIGHT, RESET)
+        );
+    }
+}
@@ -0,0 +1,356 @@
+//! Disjoint set union, following the AtCoder Library API, with rollback and weighted variants.
+use std::ops::{Add, Sub};
+
+use crate::num::Zero;
+
+/// Union by size with path compression, in amortized `O(α(n))` per operation.
+#[derive(Debug, Clone)]
+pub struct Dsu {
+    /// The parent of a non-root, or the negated size of a root's set.
+    parent_or_size: Vec<isize>,
+}
+
+impl Dsu {
+    pub fn new(n: usize) -> Self {
+        Dsu {
+            parent_or_size: vec![-1; n],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent_or_size.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent_or_size.is_empty()
+    }
+
+    /// Merge the sets of `a` and `b`, returning the new leader.
+    pub fn merge(&mut self, a: usize, b: usize) -> usize {
+        let (mut x, mut y) = (self.leader(a), self.leader(b));
+        if x == y {
+            return x;
+        }
+        if self.parent_or_size[x] > self.parent_or_size[y] {
+            std::mem::swap(&mut x, &mut y);
+        }
+        self.parent_or_size[x] += self.parent_or_size[y];
+        self.parent_or_size[y] = x as isize;
+        x
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.leader(a) == self.leader(b)
+    }
+
+    pub fn leader(&mut self, a: usize) -> usize {
+        let mut root = a;
+        while self.parent_or_size[root] >= 0 {
+            root = self.parent_or_size[root] as usize;
+        }
+        let mut v = a;
+        while v != root {
+            let next = self.parent_or_size[v] as usize;
+            self.parent_or_size[v] = root as isize;
+            v = next;
+        }
+        root
+    }
+
+    /// Size of the set containing `a`.
+    pub fn size(&mut self, a: usize) -> usize {
+        let x = self.leader(a);
+        -self.parent_or_size[x] as usize
+    }
+
+    /// The sets, each in increasing order, ordered by their smallest element.
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        let n = self.len();
+        let mut index = vec![usize::MAX; n];
+        let mut groups: Vec<Vec<usize>> = vec![];
+        for v in 0..n {
+            let x = self.leader(v);
+            if index[x] == usize::MAX {
+                index[x] = groups.len();
+                groups.push(vec![]);
+            }
+            groups[index[x]].push(v);
+        }
+        groups
+    }
+}
+
+/// Union by size without path compression, in `O(log n)` per operation, whose merges can be
+/// undone in reverse order, e.g. for offline dynamic connectivity.
+#[derive(Debug, Clone)]
+pub struct RollbackDsu {
+    parent_or_size: Vec<isize>,
+    /// The roots attached by each merge with their negated set sizes, or `None` for merges
+    /// within a set.
+    history: Vec<Option<(usize, isize)>>,
+    components: usize,
+}
+
+impl RollbackDsu {
+    pub fn new(n: usize) -> Self {
+        RollbackDsu {
+            parent_or_size: vec![-1; n],
+            history: vec![],
+            components: n,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent_or_size.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent_or_size.is_empty()
+    }
+
+    /// Merge the sets of `a` and `b`, returning the new leader. Every call, even one that
+    /// merges nothing, can be undone by `undo`.
+    pub fn merge(&mut self, a: usize, b: usize) -> usize {
+        let (mut x, mut y) = (self.leader(a), self.leader(b));
+        if x == y {
+            self.history.push(None);
+            return x;
+        }
+        if self.parent_or_size[x] > self.parent_or_size[y] {
+            std::mem::swap(&mut x, &mut y);
+        }
+        self.history.push(Some((y, self.parent_or_size[y])));
+        self.parent_or_size[x] += self.parent_or_size[y];
+        self.parent_or_size[y] = x as isize;
+        self.components -= 1;
+        x
+    }
+
+    pub fn same(&self, a: usize, b: usize) -> bool {
+        self.leader(a) == self.leader(b)
+    }
+
+    pub fn leader(&self, mut a: usize) -> usize {
+        while self.parent_or_size[a] >= 0 {
+            a = self.parent_or_size[a] as usize;
+        }
+        a
+    }
+
+    pub fn size(&self, a: usize) -> usize {
+        -self.parent_or_size[self.leader(a)] as usize
+    }
+
+    /// Number of sets.
+    pub fn components(&self) -> usize {
+        self.components
+    }
+
+    /// Undo the last merge, returning false if there is none.
+    pub fn undo(&mut self) -> bool {
+        let Some(last) = self.history.pop() else {
+            return false;
+        };
+        if let Some((y, size)) = last {
+            let x = self.parent_or_size[y] as usize;
+            self.parent_or_size[x] -= size;
+            self.parent_or_size[y] = size;
+            self.components += 1;
+        }
+        true
+    }
+
+    /// The number of merges so far, to pass to `rollback` later.
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Undo the merges made since `snapshot` returned `time`.
+    pub fn rollback(&mut self, time: usize) {
+        assert!(time <= self.history.len());
+        while self.history.len() > time {
+            self.undo();
+        }
+    }
+}
+
+/// Union-find that also tracks the potential difference `p[b] - p[a]` between elements of the
+/// same set, e.g. for checking the consistency of relations `p[b] - p[a] = d`.
+///
+/// The element of lowest potential is the leader of its set, so every stored weight is
+/// non-negative and unsigned `T` works as long as `diff` is only asked for non-negative
+/// differences. Sets are therefore not merged by size; path compression alone keeps operations
+/// at `O(log n)` amortized.
+#[derive(Debug, Clone)]
+pub struct WeightedDsu<T> {
+    parent_or_size: Vec<isize>,
+    /// The potential of each element relative to its parent.
+    weight: Vec<T>,
+}
+
+impl<T: Copy + PartialOrd + Zero + Add<Output = T> + Sub<Output = T>> WeightedDsu<T> {
+    pub fn new(n: usize) -> Self {
+        WeightedDsu {
+            parent_or_size: vec![-1; n],
+            weight: vec![T::zero(); n],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent_or_size.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent_or_size.is_empty()
+    }
+
+    /// Record `p[b] - p[a] = d`, returning false if it contradicts the earlier relations, in
+    /// which case nothing changes.
+    pub fn merge(&mut self, a: usize, b: usize, d: T) -> bool {
+        let (x, y) = (self.leader(a), self.leader(b));
+        // p[y] - p[x] = (d + (p[a] - p[x])) - (p[b] - p[y]), compared before subtracting.
+        let (high, low) = (d + self.weight[a], self.weight[b]);
+        if x == y {
+            return high == low;
+        }
+        let (root, child, weight) = if high >= low {
+            (x, y, high - low)
+        } else {
+            (y, x, low - high)
+        };
+        self.parent_or_size[root] += self.parent_or_size[child];
+        self.parent_or_size[child] = root as isize;
+        self.weight[child] = weight;
+        true
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.leader(a) == self.leader(b)
+    }
+
+    pub fn leader(&mut self, a: usize) -> usize {
+        let mut root = a;
+        let mut total = T::zero();
+        while self.parent_or_size[root] >= 0 {
+            total = total + self.weight[root];
+            root = self.parent_or_size[root] as usize;
+        }
+        // `total` is the potential of `v` relative to the root, for each `v` on the path.
+        let mut v = a;
+        while v != root {
+            let next = self.parent_or_size[v] as usize;
+            let weight = self.weight[v];
+            self.weight[v] = total;
+            self.parent_or_size[v] = root as isize;
+            total = total - weight;
+            v = next;
+        }
+        root
+    }
+
+    pub fn size(&mut self, a: usize) -> usize {
+        let x = self.leader(a);
+        -self.parent_or_size[x] as usize
+    }
+
+    /// `p[b] - p[a]`, or `None` if `a` and `b` are in different sets.
+    pub fn diff(&mut self, a: usize, b: usize) -> Option<T> {
+        if self.same(a, b) {
+            Some(self.weight[b] - self.weight[a])
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dsu() {
+        let mut dsu = Dsu::new(6);
+        assert_eq!(dsu.merge(0, 3), dsu.leader(3));
+        dsu.merge(4, 3);
+        dsu.merge(5, 1);
+        assert!(dsu.same(0, 4));
+        assert!(!dsu.same(1, 2));
+        assert_eq!(dsu.size(4), 3);
+        assert_eq!(dsu.size(2), 1);
+        assert_eq!(dsu.groups(), [vec![0, 3, 4], vec![1, 5], vec![2]]);
+
+        // A long chain, which must not overflow the stack.
+        let n = 1_000_000;
+        let mut dsu = Dsu::new(n);
+        for v in 1..n {
+            dsu.merge(v - 1, v);
+        }
+        assert_eq!(dsu.size(n - 1), n);
+    }
+
+    #[test]
+    fn test_rollback_dsu() {
+        let mut dsu = RollbackDsu::new(5);
+        dsu.merge(0, 1);
+        let time = dsu.snapshot();
+        dsu.merge(2, 3);
+        dsu.merge(1, 3);
+        dsu.merge(0, 2);
+        assert_eq!(dsu.size(2), 4);
+        assert_eq!(dsu.components(), 2);
+        assert!(dsu.undo());
+        assert!(dsu.same(0, 2));
+        dsu.undo();
+        assert!(!dsu.same(0, 2));
+        assert!(dsu.same(2, 3));
+        assert_eq!(dsu.size(3), 2);
+        dsu.rollback(time);
+        assert!(!dsu.same(2, 3));
+        assert_eq!((dsu.size(0), dsu.size(2), dsu.components()), (2, 1, 4));
+        dsu.rollback(0);
+        assert_eq!(dsu.components(), 5);
+        assert!(!dsu.undo());
+    }
+
+    #[test]
+    fn test_weighted_dsu() {
+        let mut dsu = WeightedDsu::new(5);
+        assert!(dsu.merge(0, 1, 3i64));
+        assert!(dsu.merge(2, 1, -2));
+        assert!(dsu.merge(3, 4, 10));
+        assert_eq!(dsu.diff(0, 2), Some(5));
+        assert_eq!(dsu.diff(2, 0), Some(-5));
+        assert_eq!(dsu.diff(0, 4), None);
+        assert!(dsu.merge(2, 3, 1));
+        assert_eq!(dsu.diff(0, 4), Some(16));
+        assert!(dsu.merge(4, 1, -13));
+        assert!(!dsu.merge(4, 0, 0));
+        assert_eq!(dsu.diff(4, 0), Some(-16));
+        assert_eq!(dsu.size(3), 5);
+
+        // A long chain with `p[v] = v`.
+        let n = 100_000;
+        let mut dsu = WeightedDsu::new(n);
+        for v in (1..n).rev() {
+            assert!(dsu.merge(v - 1, v, 1i64));
+        }
+        assert_eq!(dsu.diff(0, n - 1), Some(n as i64 - 1));
+        assert_eq!(dsu.diff(n / 2, 7), Some(7 - (n / 2) as i64));
+        assert!(!dsu.merge(3, 5, 3));
+    }
+
+    #[test]
+    fn test_weighted_dsu_unsigned() {
+        let mut dsu = WeightedDsu::new(4);
+        assert!(dsu.merge(0, 1, 5u64));
+        assert!(dsu.merge(2, 1, 2));
+        assert_eq!(dsu.diff(0, 2), Some(3));
+        assert!(!dsu.merge(2, 1, 3));
+        assert!(dsu.merge(3, 0, 7));
+        assert_eq!(dsu.diff(3, 2), Some(10));
+        assert_eq!(dsu.diff(3, 1), Some(12));
+        assert_eq!(dsu.leader(1), 3);
+        assert!(dsu.merge(2, 1, 2));
+        assert!(!dsu.merge(1, 0, 0));
+    }
+}
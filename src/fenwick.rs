@@ -0,0 +1,294 @@
+//! Fenwick trees (binary indexed trees) for prefix sums under point and range updates.
+use std::ops::{Add, Mul, RangeBounds, Sub};
+
+use crate::num::{FromUsize, Zero};
+use crate::segtree::bounds;
+
+/// Point add and range sum in `O(log n)`.
+#[derive(Debug, Clone)]
+pub struct FenwickTree<T> {
+    /// `tree[i - 1]` holds the sum of `(i - lowbit(i))..i`.
+    tree: Vec<T>,
+}
+
+impl<T: Copy + Zero + Add<Output = T> + Sub<Output = T>> FenwickTree<T> {
+    pub fn new(n: usize) -> Self {
+        FenwickTree {
+            tree: vec![T::zero(); n],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Add `x` to the element at `p`.
+    pub fn add(&mut self, p: usize, x: T) {
+        assert!(p < self.len());
+        let mut i = p + 1;
+        while i <= self.len() {
+            self.tree[i - 1] = self.tree[i - 1] + x;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Sum of the first `r` elements.
+    pub fn prefix_sum(&self, mut r: usize) -> T {
+        assert!(r <= self.len());
+        let mut sum = T::zero();
+        while r > 0 {
+            sum = sum + self.tree[r - 1];
+            r &= r - 1;
+        }
+        sum
+    }
+
+    pub fn sum(&self, range: impl RangeBounds<usize>) -> T {
+        let (l, r) = bounds(range, self.len());
+        self.prefix_sum(r) - self.prefix_sum(l)
+    }
+
+    /// The smallest `i` such that `prefix_sum(i + 1) >= target`, or `len()` if there is none,
+    /// assuming all elements are non-negative.
+    ///
+    /// With counts of values as elements, `lower_bound(k)` is the `k`-th smallest value.
+    pub fn lower_bound(&self, mut target: T) -> usize
+    where
+        T: PartialOrd,
+    {
+        let n = self.len();
+        let mut i = 0;
+        let mut step = if n == 0 { 0 } else { 1 << n.ilog2() };
+        while step > 0 {
+            if i + step <= n && self.tree[i + step - 1] < target {
+                i += step;
+                target = target - self.tree[i - 1];
+            }
+            step >>= 1;
+        }
+        i
+    }
+}
+
+impl<T: Copy + Zero + Add<Output = T> + Sub<Output = T>> From<Vec<T>> for FenwickTree<T> {
+    /// Build in `O(n)`.
+    fn from(mut tree: Vec<T>) -> Self {
+        for i in 1..=tree.len() {
+            let parent = i + (i & i.wrapping_neg());
+            if parent <= tree.len() {
+                tree[parent - 1] = tree[parent - 1] + tree[i - 1];
+            }
+        }
+        FenwickTree { tree }
+    }
+}
+
+/// Range add and range sum in `O(log n)`, with Fenwick trees over the differences.
+///
+/// The positive and negative parts of the differences are kept apart, so that unsigned types
+/// never go below zero.
+#[derive(Debug, Clone)]
+pub struct RangeFenwickTree<T> {
+    /// Increases `d[i] = a[i] - a[i - 1]` where a range starts.
+    diff_add: FenwickTree<T>,
+    /// Decreases `a[i - 1] - a[i]` where a range ends.
+    diff_sub: FenwickTree<T>,
+    /// `diff_add[i] * i`.
+    weighted_add: FenwickTree<T>,
+    /// `diff_sub[i] * i`.
+    weighted_sub: FenwickTree<T>,
+}
+
+impl<T> RangeFenwickTree<T>
+where
+    T: Copy + Zero + FromUsize + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    pub fn new(n: usize) -> Self {
+        RangeFenwickTree {
+            diff_add: FenwickTree::new(n + 1),
+            diff_sub: FenwickTree::new(n + 1),
+            weighted_add: FenwickTree::new(n + 1),
+            weighted_sub: FenwickTree::new(n + 1),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.diff_add.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add `x` to every element in `range`.
+    pub fn add(&mut self, range: impl RangeBounds<usize>, x: T) {
+        let (l, r) = bounds(range, self.len());
+        self.diff_add.add(l, x);
+        self.diff_sub.add(r, x);
+        self.weighted_add.add(l, x * T::from_usize(l));
+        self.weighted_sub.add(r, x * T::from_usize(r));
+    }
+
+    /// Sum of the first `r` elements: `sum(d[i] * (r - i))` over `i < r`.
+    pub fn prefix_sum(&self, r: usize) -> T {
+        let n = T::from_usize(r);
+        (self.diff_add.prefix_sum(r) * n + self.weighted_sub.prefix_sum(r))
+            - (self.diff_sub.prefix_sum(r) * n + self.weighted_add.prefix_sum(r))
+    }
+
+    pub fn sum(&self, range: impl RangeBounds<usize>) -> T {
+        let (l, r) = bounds(range, self.len());
+        self.prefix_sum(r) - self.prefix_sum(l)
+    }
+}
+
+/// Point add and rectangle sum on a grid in `O(log h log w)`.
+#[derive(Debug, Clone)]
+pub struct FenwickTree2D<T> {
+    h: usize,
+    w: usize,
+    tree: Vec<T>,
+}
+
+impl<T: Copy + Zero + Add<Output = T> + Sub<Output = T>> FenwickTree2D<T> {
+    pub fn new(h: usize, w: usize) -> Self {
+        FenwickTree2D {
+            h,
+            w,
+            tree: vec![T::zero(); h * w],
+        }
+    }
+
+    pub fn add(&mut self, i: usize, j: usize, x: T) {
+        assert!(i < self.h && j < self.w);
+        let mut a = i + 1;
+        while a <= self.h {
+            let mut b = j + 1;
+            while b <= self.w {
+                let k = (a - 1) * self.w + b - 1;
+                self.tree[k] = self.tree[k] + x;
+                b += b & b.wrapping_neg();
+            }
+            a += a & a.wrapping_neg();
+        }
+    }
+
+    /// Sum of the rectangle `0..i` by `0..j`.
+    pub fn prefix_sum(&self, i: usize, j: usize) -> T {
+        assert!(i <= self.h && j <= self.w);
+        let mut sum = T::zero();
+        let mut a = i;
+        while a > 0 {
+            let mut b = j;
+            while b > 0 {
+                sum = sum + self.tree[(a - 1) * self.w + b - 1];
+                b &= b - 1;
+            }
+            a &= a - 1;
+        }
+        sum
+    }
+
+    pub fn sum(&self, rows: impl RangeBounds<usize>, cols: impl RangeBounds<usize>) -> T {
+        let (i0, i1) = bounds(rows, self.h);
+        let (j0, j1) = bounds(cols, self.w);
+        // Added before subtracting, so that unsigned types never go below zero.
+        (self.prefix_sum(i1, j1) + self.prefix_sum(i0, j0))
+            - (self.prefix_sum(i0, j1) + self.prefix_sum(i1, j0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fenwick() {
+        let mut a = vec![3i64, 1, 4, 1, 5, 9, 2];
+        let mut tree = FenwickTree::from(a.clone());
+        assert_eq!(tree.len(), 7);
+        tree.add(2, -10);
+        a[2] -= 10;
+        for l in 0..=a.len() {
+            for r in l..=a.len() {
+                assert_eq!(tree.sum(l..r), a[l..r].iter().sum::<i64>());
+            }
+        }
+        assert_eq!(tree.sum(..), 15);
+        assert_eq!(FenwickTree::<f64>::new(3).sum(1..=2), 0.0);
+
+        // Counts of the values 0..8: the multiset {1, 1, 3, 6}.
+        let mut counts = FenwickTree::new(8);
+        for v in [1, 3, 1, 6] {
+            counts.add(v, 1u32);
+        }
+        let kth: Vec<usize> = (1..=5).map(|k| counts.lower_bound(k)).collect();
+        assert_eq!(kth, [1, 1, 3, 6, 8]);
+        assert_eq!(counts.lower_bound(0), 0);
+        assert_eq!(FenwickTree::<u32>::new(0).lower_bound(1), 0);
+    }
+
+    #[test]
+    fn test_range_fenwick() {
+        let mut a = [0i64; 6];
+        let mut tree = RangeFenwickTree::new(6);
+        for (l, r, x) in [(1, 4, 3), (0, 6, -1), (3, 3, 7), (2, 6, 5)] {
+            tree.add(l..r, x);
+            for v in &mut a[l..r] {
+                *v += x;
+            }
+            for l in 0..=a.len() {
+                for r in l..=a.len() {
+                    assert_eq!(tree.sum(l..r), a[l..r].iter().sum::<i64>());
+                }
+            }
+        }
+
+        let mut a = [0u64; 5];
+        let mut tree = RangeFenwickTree::new(5);
+        for (l, r, x) in [(1, 3, 2), (0, 5, 1), (2, 4, 7)] {
+            tree.add(l..r, x);
+            for v in &mut a[l..r] {
+                *v += x;
+            }
+        }
+        for l in 0..=a.len() {
+            for r in l..=a.len() {
+                assert_eq!(tree.sum(l..r), a[l..r].iter().sum::<u64>());
+            }
+        }
+    }
+
+    #[test]
+    fn test_fenwick_2d() {
+        let mut grid = vec![vec![0i32; 4]; 3];
+        let mut tree = FenwickTree2D::new(3, 4);
+        for (i, j, x) in [(0, 0, 1), (1, 2, 5), (2, 3, -2), (1, 2, 1), (2, 0, 4)] {
+            tree.add(i, j, x);
+            grid[i][j] += x;
+        }
+        for i0 in 0..=3 {
+            for i1 in i0..=3 {
+                for j0 in 0..=4 {
+                    for j1 in j0..=4 {
+                        let expected: i32 = grid[i0..i1]
+                            .iter()
+                            .map(|row| row[j0..j1].iter().sum::<i32>())
+                            .sum();
+                        assert_eq!(tree.sum(i0..i1, j0..j1), expected);
+                    }
+                }
+            }
+        }
+
+        let mut tree = FenwickTree2D::new(2, 2);
+        tree.add(0, 0, 5u32);
+        assert_eq!(tree.sum(1..2, 1..2), 0);
+        assert_eq!(tree.sum(..1, ..), 5);
+        assert_eq!(tree.sum(1.., ..), 0);
+    }
+}
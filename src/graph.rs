@@ -0,0 +1,581 @@
+//! Graph traversal and decomposition.
+//!
+//! Vertices are `0..n` and edges are numbered in the order they were given, so per-edge data
+//! such as weights lives in a separate `Vec` indexed by edge id.
+use std::collections::VecDeque;
+
+/// Adjacency in compressed sparse row form: the edges out of each vertex are contiguous.
+///
+/// An undirected edge is listed at both its endpoints, with the same id.
+#[derive(Debug, Clone)]
+pub struct Graph {
+    directed: bool,
+    edges: Vec<(usize, usize)>,
+    start: Vec<usize>,
+    /// `(neighbor, edge id)`, grouped by vertex.
+    adj: Vec<(usize, usize)>,
+}
+
+impl Graph {
+    pub fn directed(n: usize, edges: &[(usize, usize)]) -> Graph {
+        Graph::build(n, edges, true)
+    }
+
+    pub fn undirected(n: usize, edges: &[(usize, usize)]) -> Graph {
+        Graph::build(n, edges, false)
+    }
+
+    fn build(n: usize, edges: &[(usize, usize)], directed: bool) -> Graph {
+        let mut start = vec![0; n + 1];
+        for &(u, v) in edges {
+            assert!(u < n && v < n, "edge ({}, {}) out of range", u, v);
+            start[u + 1] += 1;
+            if !directed {
+                start[v + 1] += 1;
+            }
+        }
+        for v in 0..n {
+            start[v + 1] += start[v];
+        }
+        let mut next = start.clone();
+        let mut adj = vec![(0, 0); start[n]];
+        for (id, &(u, v)) in edges.iter().enumerate() {
+            adj[next[u]] = (v, id);
+            next[u] += 1;
+            if !directed {
+                adj[next[v]] = (u, id);
+                next[v] += 1;
+            }
+        }
+        Graph {
+            directed,
+            edges: edges.to_vec(),
+            start,
+            adj,
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn num_vertices(&self) -> usize {
+        self.start.len() - 1
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Endpoints of an edge, as given.
+    pub fn edge(&self, id: usize) -> (usize, usize) {
+        self.edges[id]
+    }
+
+    /// `(neighbor, edge id)` of the edges out of `v`.
+    pub fn adjacent(&self, v: usize) -> &[(usize, usize)] {
+        &self.adj[self.start[v]..self.start[v + 1]]
+    }
+
+    pub fn neighbors(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        self.adjacent(v).iter().map(|&(w, _)| w)
+    }
+
+    /// Number of edges out of `v`; a self-loop counts twice in an undirected graph.
+    pub fn degree(&self, v: usize) -> usize {
+        self.start[v + 1] - self.start[v]
+    }
+}
+
+/// Vertices reachable from a root, in depth-first order.
+#[derive(Debug, Clone)]
+pub struct Dfs {
+    pub preorder: Vec<usize>,
+    /// Every vertex comes after all its descendants.
+    pub postorder: Vec<usize>,
+    /// Parent in the DFS tree, `None` for the root and unreached vertices.
+    pub parent: Vec<Option<usize>>,
+}
+
+/// Depth-first search from `root` with an explicit stack, so deep graphs cannot overflow.
+pub fn dfs(g: &Graph, root: usize) -> Dfs {
+    let n = g.num_vertices();
+    let mut visited = vec![false; n];
+    let mut parent = vec![None; n];
+    let mut preorder = Vec::new();
+    let mut postorder = Vec::new();
+    // (vertex, index of the next edge to try)
+    let mut stack = vec![(root, 0)];
+    visited[root] = true;
+    preorder.push(root);
+    while let Some((v, i)) = stack.last_mut() {
+        let v = *v;
+        if let Some(&(w, _)) = g.adjacent(v).get(*i) {
+            *i += 1;
+            if !visited[w] {
+                visited[w] = true;
+                parent[w] = Some(v);
+                preorder.push(w);
+                stack.push((w, 0));
+            }
+        } else {
+            postorder.push(v);
+            stack.pop();
+        }
+    }
+    Dfs {
+        preorder,
+        postorder,
+        parent,
+    }
+}
+
+/// Vertices reachable from the sources, by distance.
+#[derive(Debug, Clone)]
+pub struct Bfs {
+    /// Vertices in the order they were reached.
+    pub order: Vec<usize>,
+    /// Number of edges from the nearest source, `None` for unreached vertices.
+    pub dist: Vec<Option<usize>>,
+    /// Previous vertex on a shortest path, `None` for sources and unreached vertices.
+    pub parent: Vec<Option<usize>>,
+}
+
+pub fn bfs(g: &Graph, sources: &[usize]) -> Bfs {
+    let n = g.num_vertices();
+    let mut dist = vec![None; n];
+    let mut parent = vec![None; n];
+    let mut order = Vec::new();
+    let mut queue = VecDeque::new();
+    for &s in sources {
+        if dist[s].is_none() {
+            dist[s] = Some(0);
+            queue.push_back(s);
+        }
+    }
+    while let Some(v) = queue.pop_front() {
+        order.push(v);
+        let d = dist[v].expect("queued vertices are reached");
+        for w in g.neighbors(v) {
+            if dist[w].is_none() {
+                dist[w] = Some(d + 1);
+                parent[w] = Some(v);
+                queue.push_back(w);
+            }
+        }
+    }
+    Bfs {
+        order,
+        dist,
+        parent,
+    }
+}
+
+/// Vertices of a directed graph such that every edge goes forward, or `None` if there is a
+/// cycle.
+pub fn toposort(g: &Graph) -> Option<Vec<usize>> {
+    assert!(g.is_directed(), "toposort needs a directed graph");
+    let n = g.num_vertices();
+    let mut indegree = vec![0; n];
+    for &(_, v) in &g.edges {
+        indegree[v] += 1;
+    }
+    let mut order: Vec<usize> = (0..n).filter(|&v| indegree[v] == 0).collect();
+    let mut i = 0;
+    while i < order.len() {
+        let v = order[i];
+        i += 1;
+        for w in g.neighbors(v) {
+            indegree[w] -= 1;
+            if indegree[w] == 0 {
+                order.push(w);
+            }
+        }
+    }
+    (order.len() == n).then_some(order)
+}
+
+/// Strongly connected component of every vertex, by Tarjan's algorithm, with components
+/// numbered in topological order; returns the number of components and the ids.
+pub fn scc_ids(g: &Graph) -> (usize, Vec<usize>) {
+    assert!(g.is_directed(), "scc needs a directed graph");
+    let n = g.num_vertices();
+    const UNSEEN: usize = usize::MAX;
+    let mut index = vec![UNSEEN; n];
+    let mut low = vec![0; n];
+    let mut ids = vec![UNSEEN; n];
+    let mut next_index = 0;
+    let mut count = 0;
+    let mut component = Vec::new();
+    for root in 0..n {
+        if index[root] != UNSEEN {
+            continue;
+        }
+        let mut stack = vec![(root, 0)];
+        index[root] = next_index;
+        low[root] = next_index;
+        next_index += 1;
+        component.push(root);
+        while let Some((v, i)) = stack.last_mut() {
+            let v = *v;
+            if let Some(&(w, _)) = g.adjacent(v).get(*i) {
+                *i += 1;
+                if index[w] == UNSEEN {
+                    index[w] = next_index;
+                    low[w] = next_index;
+                    next_index += 1;
+                    component.push(w);
+                    stack.push((w, 0));
+                } else if ids[w] == UNSEEN {
+                    low[v] = low[v].min(index[w]);
+                }
+                continue;
+            }
+            stack.pop();
+            if let Some(&(parent, _)) = stack.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+            if low[v] == index[v] {
+                while let Some(w) = component.pop() {
+                    ids[w] = count;
+                    if w == v {
+                        break;
+                    }
+                }
+                count += 1;
+            }
+        }
+    }
+    // Tarjan finds sink components first.
+    for id in &mut ids {
+        *id = count - 1 - *id;
+    }
+    (count, ids)
+}
+
+/// Strongly connected components, in topological order.
+pub fn scc(g: &Graph) -> Vec<Vec<usize>> {
+    let (count, ids) = scc_ids(g);
+    let mut components = vec![Vec::new(); count];
+    for (v, &id) in ids.iter().enumerate() {
+        components[id].push(v);
+    }
+    components
+}
+
+/// Bridges and articulation points of an undirected graph, from the discovery time and
+/// low-link of every vertex.
+fn lowlink(g: &Graph) -> (Vec<usize>, Vec<usize>) {
+    assert!(!g.is_directed(), "bridges need an undirected graph");
+    let n = g.num_vertices();
+    const UNSEEN: usize = usize::MAX;
+    let mut order = vec![UNSEEN; n];
+    let mut low = vec![0; n];
+    let mut time = 0;
+    let mut bridges = Vec::new();
+    let mut is_cut = vec![false; n];
+    for root in 0..n {
+        if order[root] != UNSEEN {
+            continue;
+        }
+        order[root] = time;
+        low[root] = time;
+        time += 1;
+        let mut root_children = 0;
+        // (vertex, id of the tree edge into it, index of the next edge to try)
+        let mut stack = vec![(root, UNSEEN, 0)];
+        while let Some((v, into, i)) = stack.last_mut() {
+            let (v, into) = (*v, *into);
+            if let Some(&(w, id)) = g.adjacent(v).get(*i) {
+                *i += 1;
+                // Skip only the tree edge itself, so parallel edges count as back edges.
+                if id == into {
+                    continue;
+                }
+                if order[w] == UNSEEN {
+                    order[w] = time;
+                    low[w] = time;
+                    time += 1;
+                    stack.push((w, id, 0));
+                } else {
+                    low[v] = low[v].min(order[w]);
+                }
+                continue;
+            }
+            stack.pop();
+            if let Some(&(parent, _, _)) = stack.last() {
+                low[parent] = low[parent].min(low[v]);
+                if low[v] > order[parent] {
+                    bridges.push(into);
+                }
+                if parent == root {
+                    root_children += 1;
+                } else if low[v] >= order[parent] {
+                    is_cut[parent] = true;
+                }
+            }
+        }
+        is_cut[root] = root_children >= 2;
+    }
+    bridges.sort_unstable();
+    let cuts = (0..n).filter(|&v| is_cut[v]).collect();
+    (bridges, cuts)
+}
+
+/// Ids of the edges of an undirected graph whose removal disconnects their endpoints.
+pub fn bridges(g: &Graph) -> Vec<usize> {
+    lowlink(g).0
+}
+
+/// Vertices of an undirected graph whose removal disconnects their component.
+pub fn articulation_points(g: &Graph) -> Vec<usize> {
+    lowlink(g).1
+}
+
+/// Boolean formula in conjunctive normal form with two literals per clause.
+#[derive(Debug, Clone)]
+pub struct TwoSat {
+    n: usize,
+    implications: Vec<(usize, usize)>,
+}
+
+impl TwoSat {
+    pub fn new(n: usize) -> TwoSat {
+        TwoSat {
+            n,
+            implications: Vec::new(),
+        }
+    }
+
+    /// Require `x[i] == f || x[j] == g`.
+    pub fn add_clause(&mut self, i: usize, f: bool, j: usize, g: bool) {
+        assert!(i < self.n && j < self.n);
+        let literal = |k: usize, value: bool| 2 * k + usize::from(value);
+        self.implications.push((literal(i, !f), literal(j, g)));
+        self.implications.push((literal(j, !g), literal(i, f)));
+    }
+
+    /// An assignment satisfying every clause, if there is one.
+    pub fn solve(&self) -> Option<Vec<bool>> {
+        let g = Graph::directed(2 * self.n, &self.implications);
+        let (_, ids) = scc_ids(&g);
+        (0..self.n)
+            .map(|i| {
+                let (negative, positive) = (ids[2 * i], ids[2 * i + 1]);
+                // A literal is true if the other one implies it, i.e. it comes later.
+                (negative != positive).then_some(positive > negative)
+            })
+            .collect()
+    }
+}
+
+/// A walk through a graph, as vertices and the ids of the edges between them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trail {
+    /// One more than the edges; the first and last coincide for a circuit.
+    pub vertices: Vec<usize>,
+    pub edges: Vec<usize>,
+}
+
+/// A trail using every edge exactly once, by Hierholzer's algorithm, or `None` if there is
+/// none. Vertices without edges are ignored.
+pub fn euler_path(g: &Graph) -> Option<Trail> {
+    let n = g.num_vertices();
+    if n == 0 {
+        return Some(Trail {
+            vertices: Vec::new(),
+            edges: Vec::new(),
+        });
+    }
+    // Excess of out- over in-degree, or degree parity when undirected.
+    let mut excess = vec![0i64; n];
+    for &(u, v) in &g.edges {
+        excess[u] += 1;
+        excess[v] += if g.directed { -1 } else { 1 };
+    }
+    let start = if g.directed {
+        let sources: Vec<usize> = (0..n).filter(|&v| excess[v] != 0).collect();
+        match sources[..] {
+            [] => None,
+            [a, b] if excess[a] + excess[b] == 0 && excess[a].abs() == 1 => {
+                Some(if excess[a] == 1 { a } else { b })
+            }
+            _ => return None,
+        }
+    } else {
+        let odd: Vec<usize> = (0..n).filter(|&v| excess[v] % 2 == 1).collect();
+        match odd[..] {
+            [] => None,
+            [a, _] => Some(a),
+            _ => return None,
+        }
+    };
+    let start = start
+        .or_else(|| (0..n).find(|&v| g.degree(v) > 0))
+        .unwrap_or(0);
+    hierholzer(g, start)
+}
+
+/// A closed trail using every edge exactly once, or `None` if there is none.
+pub fn euler_circuit(g: &Graph) -> Option<Trail> {
+    euler_path(g).filter(|trail| trail.vertices.first() == trail.vertices.last())
+}
+
+fn hierholzer(g: &Graph, start: usize) -> Option<Trail> {
+    let mut used = vec![false; g.num_edges()];
+    let mut next = g.start[..g.num_vertices()].to_vec();
+    let mut vertices = Vec::new();
+    let mut edges = Vec::new();
+    // (vertex, edge taken into it)
+    let mut stack = vec![(start, usize::MAX)];
+    while let Some(&(v, into)) = stack.last() {
+        let mut advanced = false;
+        while next[v] < g.start[v + 1] {
+            let (w, id) = g.adj[next[v]];
+            next[v] += 1;
+            if !used[id] {
+                used[id] = true;
+                stack.push((w, id));
+                advanced = true;
+                break;
+            }
+        }
+        if !advanced {
+            stack.pop();
+            vertices.push(v);
+            if into != usize::MAX {
+                edges.push(into);
+            }
+        }
+    }
+    // Edges left over belong to another component.
+    if edges.len() < g.num_edges() {
+        return None;
+    }
+    vertices.reverse();
+    edges.reverse();
+    Some(Trail { vertices, edges })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_trail(g: &Graph, trail: &Trail) {
+        assert_eq!(trail.vertices.len(), trail.edges.len() + 1);
+        let mut edges = trail.edges.clone();
+        edges.sort_unstable();
+        assert_eq!(edges, (0..g.num_edges()).collect::<Vec<_>>());
+        for (i, &id) in trail.edges.iter().enumerate() {
+            let (u, v) = g.edge(id);
+            let step = (trail.vertices[i], trail.vertices[i + 1]);
+            assert!(step == (u, v) || (!g.is_directed() && step == (v, u)));
+        }
+    }
+
+    #[test]
+    fn test_graph() {
+        let g = Graph::undirected(4, &[(0, 1), (1, 2), (1, 1)]);
+        assert_eq!(g.num_vertices(), 4);
+        assert_eq!(g.num_edges(), 3);
+        assert_eq!(g.adjacent(1), [(0, 0), (2, 1), (1, 2), (1, 2)]);
+        assert_eq!(g.degree(3), 0);
+        let g = Graph::directed(3, &[(2, 0), (0, 1), (2, 1)]);
+        assert_eq!(g.neighbors(2).collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(g.edge(1), (0, 1));
+    }
+
+    #[test]
+    fn test_traversal() {
+        let g = Graph::undirected(6, &[(0, 1), (0, 2), (1, 3), (2, 3), (4, 5)]);
+        let dfs = dfs(&g, 0);
+        assert_eq!(dfs.preorder, [0, 1, 3, 2]);
+        assert_eq!(dfs.postorder, [2, 3, 1, 0]);
+        assert_eq!(dfs.parent, [None, Some(0), Some(3), Some(1), None, None]);
+        let bfs = bfs(&g, &[0]);
+        assert_eq!(bfs.order, [0, 1, 2, 3]);
+        assert_eq!(bfs.dist, [Some(0), Some(1), Some(1), Some(2), None, None]);
+        assert_eq!(bfs.parent[3], Some(1));
+
+        // A path long enough to overflow a recursive DFS.
+        let n = 1_000_000;
+        let path: Vec<_> = (1..n).map(|v| (v - 1, v)).collect();
+        let g = Graph::directed(n, &path);
+        assert_eq!(super::dfs(&g, 0).postorder[0], n - 1);
+    }
+
+    #[test]
+    fn test_toposort_and_scc() {
+        let g = Graph::directed(4, &[(2, 0), (0, 1), (2, 1), (3, 2)]);
+        assert_eq!(toposort(&g), Some(vec![3, 2, 0, 1]));
+        let g = Graph::directed(6, &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 3), (5, 4)]);
+        assert_eq!(toposort(&g), None);
+        let components = scc(&g);
+        let position = |v: usize| components.iter().position(|c| c.contains(&v)).unwrap();
+        assert_eq!(components.len(), 3);
+        assert!(components.contains(&vec![0, 1, 2]));
+        assert!(components.contains(&vec![3, 4]));
+        assert!(position(0) < position(3));
+        assert!(position(5) < position(3));
+    }
+
+    #[test]
+    fn test_bridges() {
+        // Two triangles joined by the edge 2-3, plus a double edge 5-6 and a pendant 6-7.
+        let g = Graph::undirected(
+            8,
+            &[
+                (0, 1),
+                (1, 2),
+                (2, 0),
+                (2, 3),
+                (3, 4),
+                (4, 5),
+                (5, 3),
+                (5, 6),
+                (6, 5),
+                (6, 7),
+            ],
+        );
+        assert_eq!(bridges(&g), [3, 9]);
+        assert_eq!(articulation_points(&g), [2, 3, 5, 6]);
+        let g = Graph::undirected(3, &[(0, 1), (0, 2)]);
+        assert_eq!(articulation_points(&g), [0]);
+    }
+
+    #[test]
+    fn test_two_sat() {
+        // (x0 || x1) && (!x0 || x1) && (!x1 || !x2)
+        let mut sat = TwoSat::new(3);
+        sat.add_clause(0, true, 1, true);
+        sat.add_clause(0, false, 1, true);
+        sat.add_clause(1, false, 2, false);
+        let x = sat.solve().unwrap();
+        assert!(x[1] && !x[2]);
+        sat.add_clause(1, false, 1, false);
+        assert_eq!(sat.solve(), None);
+    }
+
+    #[test]
+    fn test_euler() {
+        let g = Graph::undirected(4, &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 2)]);
+        let trail = euler_circuit(&g).unwrap();
+        check_trail(&g, &trail);
+        let g = Graph::undirected(4, &[(0, 1), (1, 2), (2, 3), (3, 1)]);
+        assert_eq!(euler_circuit(&g), None);
+        let trail = euler_path(&g).unwrap();
+        check_trail(&g, &trail);
+        assert_eq!(trail.vertices[0], 0);
+
+        let g = Graph::directed(3, &[(0, 1), (1, 2), (2, 0), (0, 0)]);
+        check_trail(&g, &euler_circuit(&g).unwrap());
+        let g = Graph::directed(3, &[(1, 0), (1, 2), (2, 1), (0, 2)]);
+        let trail = euler_path(&g).unwrap();
+        check_trail(&g, &trail);
+        assert_eq!(trail.vertices, [1, 0, 2, 1, 2]);
+        let g = Graph::directed(4, &[(0, 1), (2, 3)]);
+        assert_eq!(euler_path(&g), None);
+        let g = Graph::undirected(3, &[]);
+        assert_eq!(euler_circuit(&g).unwrap().vertices, [0]);
+    }
+}
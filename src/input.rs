@@ -0,0 +1,328 @@
+//! Reading whitespace-separated input.
+use std::{
+    cell::RefCell,
+    io::{self, BufRead, StdinLock},
+    str::{self, FromStr},
+};
+
+/// Buffered reader of whitespace-separated tokens.
+///
+/// Tokens are read lazily, so a scanner over stdin also works for interactive problems.
+///
+/// ```
+/// use std::io::Cursor;
+///
+/// use cp_rust::input::Scanner;
+///
+/// let mut scanner = Scanner::new(Cursor::new("2\n10 -3\nhello world\n"));
+/// let n: usize = scanner.read();
+/// assert_eq!(scanner.read_vec::<i64>(n), [10, -3]);
+/// // The rest of the current line, then the next one.
+/// assert_eq!(scanner.line().as_deref(), Some(""));
+/// assert_eq!(scanner.line().as_deref(), Some("hello world"));
+/// assert_eq!(scanner.token(), None);
+/// ```
+pub struct Scanner<R> {
+    reader: R,
+    token: Vec<u8>,
+}
+
+impl<R: BufRead> Scanner<R> {
+    pub fn new(reader: R) -> Scanner<R> {
+        Scanner {
+            reader,
+            token: Vec::new(),
+        }
+    }
+
+    /// The next token, or `None` at the end of the input.
+    pub fn token(&mut self) -> Option<&[u8]> {
+        self.token.clear();
+        loop {
+            let buf = self.reader.fill_buf().expect("failed to read input");
+            if buf.is_empty() {
+                break;
+            }
+            let skip = if self.token.is_empty() {
+                buf.iter().take_while(|b| b.is_ascii_whitespace()).count()
+            } else {
+                0
+            };
+            let len = buf[skip..]
+                .iter()
+                .take_while(|b| !b.is_ascii_whitespace())
+                .count();
+            self.token.extend_from_slice(&buf[skip..skip + len]);
+            let done = skip + len < buf.len();
+            self.reader.consume(skip + len);
+            if done {
+                break;
+            }
+        }
+        if self.token.is_empty() {
+            None
+        } else {
+            Some(&self.token)
+        }
+    }
+
+    /// Parse the next token, panicking at the end of the input or on a malformed token.
+    pub fn read<T: FromStr>(&mut self) -> T {
+        let token = self.token().expect("unexpected end of input");
+        let token = str::from_utf8(token).expect("input is not valid UTF-8");
+        match token.parse() {
+            Ok(value) => value,
+            Err(_) => panic!("failed to parse `{}`", token),
+        }
+    }
+
+    pub fn bytes(&mut self) -> Vec<u8> {
+        self.token().expect("unexpected end of input").to_vec()
+    }
+
+    pub fn chars(&mut self) -> Vec<char> {
+        self.read::<String>().chars().collect()
+    }
+
+    /// Parse the next `n` tokens.
+    pub fn read_vec<T: FromStr>(&mut self, n: usize) -> Vec<T> {
+        (0..n).map(|_| self.read()).collect()
+    }
+
+    /// The rest of the current line without its line ending, or `None` at the end of the input.
+    ///
+    /// Reading a token stops right after it, so the first call after a token returns the rest
+    /// of that token's line, usually empty.
+    pub fn line(&mut self) -> Option<String> {
+        let mut line = Vec::new();
+        if self
+            .reader
+            .read_until(b'\n', &mut line)
+            .expect("failed to read input")
+            == 0
+        {
+            return None;
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+        }
+        Some(String::from_utf8(line).expect("input is not valid UTF-8"))
+    }
+}
+
+thread_local! {
+    static STDIN: RefCell<Scanner<StdinLock<'static>>> =
+        RefCell::new(Scanner::new(io::stdin().lock()));
+}
+
+/// Run `f` with the scanner over stdin shared by all `input!` calls.
+///
+/// The scanner keeps stdin locked until the thread exits, so once it is used, any other read
+/// of stdin on the same thread, e.g. `io::stdin().read_line`, blocks forever; read lines with
+/// [`Scanner::line`] instead.
+pub fn with_stdin<T>(f: impl FnOnce(&mut Scanner<StdinLock<'static>>) -> T) -> T {
+    STDIN.with(|scanner| f(&mut scanner.borrow_mut()))
+}
+
+/// Declare variables read from the input.
+///
+/// ```
+/// use std::io::Cursor;
+///
+/// use cp_rust::{input, input::Scanner};
+///
+/// let input = "3\n5 1 4\n2 7 8\nabc\n1 -1\n3 0\n2 5\n1 2\n2 3\n1 2 3\n4 5 6\n";
+/// let mut scanner = Scanner::new(Cursor::new(input));
+/// // `input! { ... }` without `from` in a solution.
+/// input! {
+///     from = &mut scanner,
+///     n: usize,
+///     mut a: [i64; n],       // `n` values
+///     b: [u64],              // a length, then as many values
+///     s: chars,              // a `Vec<char>`; `bytes` gives a `Vec<u8>`
+///     p: [(usize1, i32); n], // `usize1` turns 1-indexed into 0-indexed
+///     e: [edge1; n - 1],     // 1-indexed edges as 0-indexed `(usize, usize)`
+///     g: [[u8; 3]; 2],
+/// }
+/// a.sort();
+/// assert_eq!(a, [1, 4, 5]);
+/// assert_eq!(b, [7, 8]);
+/// assert_eq!(s, ['a', 'b', 'c']);
+/// assert_eq!(p, [(0, -1), (2, 0), (1, 5)]);
+/// assert_eq!(e, [(0, 1), (1, 2)]);
+/// assert_eq!(g, [[1, 2, 3], [4, 5, 6]]);
+/// ```
+///
+/// Values are read from stdin, or from a string with `source = s` or a [`Scanner`] with
+/// `from = &mut scanner` as the first argument.
+///
+/// Reading from stdin locks it for the rest of the thread, see [`with_stdin`]: mixing `input!`
+/// with `io::stdin()` deadlocks.
+#[macro_export]
+macro_rules! input {
+    (source = $s:expr, $($r:tt)*) => {
+        let mut scanner = $crate::input::Scanner::new(AsRef::<[u8]>::as_ref(&$s));
+        $crate::input_inner! { scanner, $($r)* }
+    };
+    (from = $scanner:expr, $($r:tt)*) => {
+        $crate::input_inner! { $scanner, $($r)* }
+    };
+    ($($r:tt)*) => {
+        $crate::input_inner! { @stdin $($r)* }
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! input_inner {
+    (@stdin) => {};
+    (@stdin ,) => {};
+    (@stdin mut $var:ident : $t:tt $($r:tt)*) => {
+        let mut $var = $crate::input::with_stdin(|scanner| $crate::read_value!(scanner, $t));
+        $crate::input_inner! { @stdin $($r)* }
+    };
+    (@stdin $var:ident : $t:tt $($r:tt)*) => {
+        let $var = $crate::input::with_stdin(|scanner| $crate::read_value!(scanner, $t));
+        $crate::input_inner! { @stdin $($r)* }
+    };
+    (@stdin , $($r:tt)*) => {
+        $crate::input_inner! { @stdin $($r)* }
+    };
+
+    ($scanner:expr) => {};
+    ($scanner:expr, ) => {};
+    ($scanner:expr, mut $var:ident : $t:tt $($r:tt)*) => {
+        let mut $var = $crate::read_value!($scanner, $t);
+        $crate::input_inner! { $scanner $($r)* }
+    };
+    ($scanner:expr, $var:ident : $t:tt $($r:tt)*) => {
+        let $var = $crate::read_value!($scanner, $t);
+        $crate::input_inner! { $scanner $($r)* }
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! read_value {
+    ($scanner:expr, ( $($t:tt),* )) => {
+        ( $($crate::read_value!($scanner, $t)),* )
+    };
+    ($scanner:expr, [ $t:tt ; $len:expr ]) => {
+        (0..$len).map(|_| $crate::read_value!($scanner, $t)).collect::<Vec<_>>()
+    };
+    ($scanner:expr, [ $t:tt ]) => {{
+        let len = $scanner.read::<usize>();
+        $crate::read_value!($scanner, [$t; len])
+    }};
+    ($scanner:expr, chars) => {
+        $scanner.chars()
+    };
+    ($scanner:expr, bytes) => {
+        $scanner.bytes()
+    };
+    ($scanner:expr, usize1) => {
+        $scanner.read::<usize>() - 1
+    };
+    ($scanner:expr, edge1) => {
+        $crate::read_value!($scanner, (usize1, usize1))
+    };
+    ($scanner:expr, $t:ty) => {
+        $scanner.read::<$t>()
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scanner() {
+        let mut scanner = Scanner::new(io::BufReader::with_capacity(
+            4,
+            &b"  12 -3\n\nabcdef  x"[..],
+        ));
+        assert_eq!(scanner.read::<u32>(), 12);
+        assert_eq!(scanner.read::<i64>(), -3);
+        assert_eq!(scanner.bytes(), b"abcdef");
+        assert_eq!(scanner.chars(), ['x']);
+        assert_eq!(scanner.token(), None);
+    }
+
+    #[test]
+    fn test_input() {
+        input! {
+            source = "3 1 2 3 2 2 0 ab 1 2 2 3 4 5 6 7 0 0.5 xy",
+            n: usize,
+            mut a: [u64; n],
+            b: [i32],
+            s: chars,
+            e: [edge1; n - 1],
+            t: (usize1, [u8; 2], char),
+            g: [[u8; 1]; 1],
+            x: f64,
+            y: bytes,
+        }
+        a.push(4);
+        assert_eq!(a, [1, 2, 3, 4]);
+        assert_eq!(b, [2, 0]);
+        assert_eq!(s, ['a', 'b']);
+        assert_eq!(e, [(0, 1), (1, 2)]);
+        assert_eq!(t, (3, vec![5, 6], '7'));
+        assert_eq!(g, [[0]]);
+        assert_eq!(x, 0.5);
+        assert_eq!(y, b"xy");
+    }
+
+    #[test]
+    fn test_lines() {
+        let mut scanner = Scanner::new(io::BufReader::with_capacity(
+            3,
+            &b"2 x\nhello  world\r\n\n 7 \nlast"[..],
+        ));
+        assert_eq!(scanner.read::<usize>(), 2);
+        assert_eq!(scanner.line().as_deref(), Some(" x"));
+        assert_eq!(scanner.line().as_deref(), Some("hello  world"));
+        assert_eq!(scanner.line().as_deref(), Some(""));
+        assert_eq!(scanner.read::<i32>(), 7);
+        assert_eq!(scanner.line().as_deref(), Some(" "));
+        assert_eq!(scanner.line().as_deref(), Some("last"));
+        assert_eq!(scanner.line(), None);
+        assert_eq!(scanner.token(), None);
+    }
+
+    #[test]
+    fn test_eof() {
+        let mut scanner = Scanner::new(&b""[..]);
+        assert_eq!(scanner.token(), None);
+        assert_eq!(scanner.line(), None);
+
+        // Trailing whitespace without a final newline.
+        let mut scanner = Scanner::new(&b"5 \t "[..]);
+        assert_eq!(scanner.read_vec::<u8>(1), [5]);
+        assert_eq!(scanner.token(), None);
+        assert_eq!(scanner.token(), None);
+
+        let mut scanner = Scanner::new(&b"1 2\n"[..]);
+        assert_eq!(scanner.read::<u8>(), 1);
+        assert_eq!(scanner.line().as_deref(), Some(" 2"));
+        assert_eq!(scanner.line(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected end of input")]
+    fn test_read_past_eof() {
+        input! {
+            source = "1",
+            _a: [u32; 2],
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "failed to parse `x`")]
+    fn test_malformed() {
+        Scanner::new(&b"x"[..]).read::<u32>();
+    }
+}
@@ -0,0 +1,308 @@
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Read, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use crate::judge::{Outcome, TestCase, Verdict};
+use crate::runner::{self, Execution, Limits};
+
+/// Who sent a line of an interaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Sent by the solution, read by the interactor.
+    Query,
+    /// Sent by the interactor, read by the solution.
+    Response,
+}
+
+/// Every line exchanged between the solution and the interactor, in order.
+///
+/// Lines are relayed as raw bytes; invalid UTF-8 is only replaced here, for display.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transcript(pub Vec<(Direction, String)>);
+
+impl fmt::Display for Transcript {
+    /// Queries are prefixed with `> `, responses with `< `.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (direction, line) in &self.0 {
+            let prefix = match direction {
+                Direction::Query => '>',
+                Direction::Response => '<',
+            };
+            writeln!(f, "{} {}", prefix, line)?;
+        }
+        Ok(())
+    }
+}
+
+/// A solution talking to an interactor through cross-connected stdin and stdout.
+///
+/// The interactor is called as `<interactor> <input> [<answer>]` and decides the verdict with
+/// its exit code: zero accepts, anything else is a wrong answer explained by its stderr.
+pub struct Interaction {
+    pub solution: PathBuf,
+    pub interactor: PathBuf,
+    pub limits: Limits,
+    /// Maximum number of lines the solution may print.
+    pub query_limit: Option<usize>,
+}
+
+impl Interaction {
+    pub fn run(&self, case: TestCase) -> io::Result<Outcome> {
+        let mut interactor = Command::new(&self.interactor);
+        interactor.arg(&case.input);
+        if let Some(ref answer) = case.answer {
+            interactor.arg(answer);
+        }
+        let mut interactor = runner::spawn(
+            interactor
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped()),
+            &self.limits,
+        )?;
+        let mut solution = runner::spawn(
+            Command::new(&self.solution)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped()),
+            &self.limits,
+        )?;
+
+        let transcript = Arc::new(Mutex::new(Transcript::default()));
+        let exceeded = Arc::new(AtomicBool::new(false));
+        let queries = relay(
+            solution.stdout.take().expect("stdout is piped"),
+            interactor.stdin.take().expect("stdin is piped"),
+            Direction::Query,
+            Arc::clone(&transcript),
+            self.query_limit.map(|limit| (limit, Arc::clone(&exceeded))),
+        );
+        let responses = relay(
+            interactor.stdout.take().expect("stdout is piped"),
+            solution.stdin.take().expect("stdin is piped"),
+            Direction::Response,
+            Arc::clone(&transcript),
+            None,
+        );
+        let mut stderr = interactor.stderr.take().expect("stderr is piped");
+        let stderr = thread::spawn(move || {
+            let mut buf = String::new();
+            stderr.read_to_string(&mut buf).map(|_| buf)
+        });
+
+        // Both run under their own limits from the start, so a hung interactor is cut off on time.
+        let limits = self.limits;
+        let checker = thread::spawn(move || runner::wait(&mut interactor, &limits));
+        let (termination, usage, killed) = runner::wait(&mut solution, &self.limits)?;
+        let (checked, _, _) = checker.join().expect("interactor wait panicked")?;
+        let relay_error = |side: &str, err: io::Error| {
+            io::Error::new(
+                err.kind(),
+                format!("failed to read from the {}: {}", side, err),
+            )
+        };
+        queries
+            .join()
+            .expect("relay panicked")
+            .map_err(|err| relay_error("solution", err))?;
+        responses
+            .join()
+            .expect("relay panicked")
+            .map_err(|err| relay_error("interactor", err))?;
+        let stderr = stderr.join().expect("stderr reader panicked")?;
+
+        let execution = Execution {
+            termination,
+            usage,
+            killed,
+            stdout: Vec::new(),
+        };
+        let (verdict, message) = match Verdict::from_execution(&execution, &self.limits) {
+            Some(verdict @ (Verdict::TimeLimitExceeded | Verdict::MemoryLimitExceeded)) => {
+                (verdict, None)
+            }
+            _ if exceeded.load(Ordering::SeqCst) => (
+                Verdict::WrongAnswer,
+                Some("query limit exceeded".to_string()),
+            ),
+            // A crashed solution leaves the interactor at EOF, whose failure is only a symptom.
+            Some(verdict) => (verdict, None),
+            None if checked != runner::Termination::Exited(0) => {
+                let mut message = stderr.trim().to_string();
+                if message.is_empty() {
+                    message = format!("interactor ended with {}", checked);
+                }
+                (Verdict::WrongAnswer, Some(message))
+            }
+            None => (Verdict::Accepted, None),
+        };
+
+        let transcript = transcript.lock().expect("transcript poisoned").to_string();
+        Ok(Outcome {
+            case,
+            verdict: Some(verdict),
+            message,
+            output: transcript,
+            usage,
+        })
+    }
+}
+
+/// Copy lines from `from` to `to` byte for byte, recording each in the transcript.
+///
+/// Once more than `limit` lines went through, the exceeded flag is set and both ends are closed.
+/// A closed `to` ends the relay quietly, as the other side may exit early; a failed read is
+/// returned.
+fn relay(
+    from: impl Read + Send + 'static,
+    mut to: impl Write + Send + 'static,
+    direction: Direction,
+    transcript: Arc<Mutex<Transcript>>,
+    limit: Option<(usize, Arc<AtomicBool>)>,
+) -> thread::JoinHandle<io::Result<()>> {
+    thread::spawn(move || {
+        let mut from = BufReader::new(from);
+        let mut line = Vec::new();
+        let mut count = 0;
+        while from.read_until(b'\n', &mut line)? > 0 {
+            count += 1;
+            if let Some((limit, ref exceeded)) = limit {
+                if count > limit {
+                    exceeded.store(true, Ordering::SeqCst);
+                    return Ok(());
+                }
+            }
+            transcript.lock().expect("transcript poisoned").0.push((
+                direction,
+                String::from_utf8_lossy(&line).trim_end().to_string(),
+            ));
+            if to.write_all(&line).and_then(|_| to.flush()).is_err() {
+                return Ok(());
+            }
+            line.clear();
+        }
+        Ok(())
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::test_util::script;
+    use crate::util::TempFile;
+
+    // Guess a number read from the input file: answers `<`, `>` or `=`.
+    const INTERACTOR: &str = r#"
+secret=$(cat "$1")
+while read guess; do
+    if [ "$guess" -lt "$secret" ]; then echo '>';
+    elif [ "$guess" -gt "$secret" ]; then echo '<';
+    else echo '='; exit 0; fi
+done
+echo 'no guess' >&2
+exit 1
+"#;
+
+    // Linear search from 1 upwards.
+    const SOLUTION: &str = r#"
+guess=1
+while true; do
+    echo $guess
+    read reply
+    [ "$reply" = '=' ] && exit 0
+    guess=$((guess + 1))
+done
+"#;
+
+    #[test]
+    fn test_interaction() {
+        let interactor = script(INTERACTOR);
+        let solution = script(SOLUTION);
+        let input = TempFile::new("in", "3\n").unwrap();
+        let mut interaction = Interaction {
+            solution: solution.0.clone(),
+            interactor: interactor.0.clone(),
+            limits: Limits::default(),
+            query_limit: None,
+        };
+
+        let outcome = interaction.run(TestCase::from_input(&input.0)).unwrap();
+        assert_eq!(outcome.verdict, Some(Verdict::Accepted));
+        assert_eq!(outcome.output, "> 1\n< >\n> 2\n< >\n> 3\n< =\n");
+
+        interaction.query_limit = Some(2);
+        let outcome = interaction.run(TestCase::from_input(&input.0)).unwrap();
+        assert_eq!(outcome.verdict, Some(Verdict::WrongAnswer));
+        assert_eq!(outcome.message.as_deref(), Some("query limit exceeded"));
+    }
+
+    #[test]
+    fn test_solution_crash() {
+        // The solution gives up after one query, so the interactor fails on EOF.
+        let interactor = script(INTERACTOR);
+        let solution = script("echo 1\nread reply\nexit 3\n");
+        let input = TempFile::new("in", "3\n").unwrap();
+        let interaction = Interaction {
+            solution: solution.0.clone(),
+            interactor: interactor.0.clone(),
+            limits: Limits::default(),
+            query_limit: None,
+        };
+
+        let outcome = interaction.run(TestCase::from_input(&input.0)).unwrap();
+        assert_eq!(
+            outcome.verdict,
+            Some(Verdict::RuntimeError(runner::Termination::Exited(3)))
+        );
+    }
+
+    #[test]
+    fn test_hung_interactor() {
+        // The interactor never answers, so the solution blocks on its first read.
+        let interactor = script("exec sleep 5\n");
+        let solution = script(SOLUTION);
+        let input = TempFile::new("in", "3\n").unwrap();
+        let interaction = Interaction {
+            solution: solution.0.clone(),
+            interactor: interactor.0.clone(),
+            limits: Limits::new(Duration::from_millis(500), 256 << 20),
+            query_limit: None,
+        };
+
+        let start = Instant::now();
+        let outcome = interaction.run(TestCase::from_input(&input.0)).unwrap();
+        assert_eq!(outcome.verdict, Some(Verdict::TimeLimitExceeded));
+        // The wall-clock limit is 1 second; waiting in turn would take twice that.
+        assert!(start.elapsed() < Duration::from_millis(1800));
+    }
+
+    #[test]
+    fn test_non_utf8() {
+        // The interactor skips a Latin-1 greeting, which must reach it unchanged.
+        let interactor = script(&format!(
+            "read greeting; [ \"$greeting\" = \"$(printf 'caf\\351')\" ] || exit 1\n{}",
+            INTERACTOR
+        ));
+        let solution = script(&format!("printf 'caf\\351\\n'\n{}", SOLUTION));
+        let input = TempFile::new("in", "1\n").unwrap();
+        let interaction = Interaction {
+            solution: solution.0.clone(),
+            interactor: interactor.0.clone(),
+            limits: Limits::default(),
+            query_limit: None,
+        };
+
+        let outcome = interaction.run(TestCase::from_input(&input.0)).unwrap();
+        assert_eq!(outcome.verdict, Some(Verdict::Accepted));
+        assert_eq!(outcome.output, "> caf\u{fffd}\n> 1\n< =\n");
+    }
+}
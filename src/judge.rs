@@ -0,0 +1,350 @@
+use std::{
+    fmt,
+    fs::{self, File},
+    io,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::checker::Checker;
+use crate::runner::{self, Execution, Limits, Termination, Usage};
+
+/// A single sample test: an input file and, optionally, the expected output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase {
+    pub name: String,
+    pub input: PathBuf,
+    pub answer: Option<PathBuf>,
+}
+
+impl TestCase {
+    /// Build a test case from an input file, picking up a sibling `.out` file if present.
+    pub fn from_input(input: &Path) -> TestCase {
+        let name = input
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let answer = input.with_extension("out");
+        TestCase {
+            name,
+            input: input.to_path_buf(),
+            answer: if answer.is_file() { Some(answer) } else { None },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Accepted,
+    WrongAnswer,
+    TimeLimitExceeded,
+    MemoryLimitExceeded,
+    RuntimeError(Termination),
+}
+
+impl Verdict {
+    pub fn short(&self) -> &'static str {
+        match self {
+            Verdict::Accepted => "AC",
+            Verdict::WrongAnswer => "WA",
+            Verdict::TimeLimitExceeded => "TLE",
+            Verdict::MemoryLimitExceeded => "MLE",
+            Verdict::RuntimeError(_) => "RE",
+        }
+    }
+
+    /// The verdict implied by how a run ended, if it did not end normally.
+    pub fn from_execution(execution: &Execution, limits: &Limits) -> Option<Verdict> {
+        if execution.exceeded_memory(limits) {
+            Some(Verdict::MemoryLimitExceeded)
+        } else if execution.exceeded_time(limits) {
+            Some(Verdict::TimeLimitExceeded)
+        } else if !execution.success() {
+            Some(Verdict::RuntimeError(execution.termination))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Verdict::RuntimeError(termination) => write!(f, "RE ({})", termination),
+            verdict => f.write_str(verdict.short()),
+        }
+    }
+}
+
+/// Result of running a program on one test case.
+#[derive(Debug)]
+pub struct Outcome {
+    pub case: TestCase,
+    pub verdict: Option<Verdict>,
+    /// Why the checker rejected the output.
+    pub message: Option<String>,
+    pub output: String,
+    pub usage: Usage,
+}
+
+/// Directory holding the sample tests of a problem, e.g. `tests/1361c`.
+pub fn test_dir(problem: &str) -> PathBuf {
+    Path::new("tests").join(problem)
+}
+
+/// Limits of a problem as stored in `limits.json` next to its tests.
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedLimits {
+    /// CPU time limit in milliseconds.
+    time_limit: u64,
+    /// Memory limit in megabytes.
+    memory_limit: u64,
+}
+
+/// Record the limits of a problem in `dir`, so later runs use them by default.
+pub fn save_limits(dir: &Path, limits: &Limits) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let saved = SavedLimits {
+        time_limit: limits.time.as_millis() as u64,
+        memory_limit: limits.memory >> 20,
+    };
+    let json = serde_json::to_string_pretty(&saved).map_err(io::Error::other)?;
+    fs::write(dir.join("limits.json"), json + "\n")
+}
+
+/// Limits recorded by [`save_limits`], if any.
+pub fn load_limits(dir: &Path) -> io::Result<Option<Limits>> {
+    let json = match fs::read_to_string(dir.join("limits.json")) {
+        Ok(json) => json,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let saved: SavedLimits = serde_json::from_str(&json).map_err(io::Error::other)?;
+    Ok(Some(Limits::new(
+        Duration::from_millis(saved.time_limit),
+        saved.memory_limit << 20,
+    )))
+}
+
+/// Collect all `*.in`/`*.out` pairs in `dir`, ordered by test number.
+pub fn discover(dir: &Path) -> io::Result<Vec<TestCase>> {
+    let mut cases = discover_inputs(dir)?;
+    cases.retain(|case| case.answer.is_some());
+    Ok(cases)
+}
+
+/// Collect all `*.in` files in `dir`, with or without an expected output.
+pub fn discover_inputs(dir: &Path) -> io::Result<Vec<TestCase>> {
+    let entries = fs::read_dir(dir).map_err(|err| match err.kind() {
+        io::ErrorKind::NotFound => io::Error::new(
+            err.kind(),
+            format!("no tests found: {} does not exist", dir.display()),
+        ),
+        _ => io::Error::new(
+            err.kind(),
+            format!("failed to read {}: {}", dir.display(), err),
+        ),
+    })?;
+    let mut cases = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "in") {
+            cases.push(TestCase::from_input(&path));
+        }
+    }
+    cases.sort_by_key(|case| {
+        (
+            case.name.parse::<u64>().unwrap_or(u64::MAX),
+            case.name.clone(),
+        )
+    });
+    Ok(cases)
+}
+
+/// Run `binary` with the input of `case` under `limits` and check its output.
+pub fn run(
+    binary: &Path,
+    case: TestCase,
+    limits: &Limits,
+    checker: &dyn Checker,
+) -> io::Result<Outcome> {
+    let stdin = Stdio::from(File::open(&case.input)?);
+    let execution = runner::run(&mut Command::new(binary), stdin, limits)?;
+    let output = String::from_utf8_lossy(&execution.stdout).to_string();
+
+    let expected = match case.answer {
+        Some(ref answer) => Some(fs::read_to_string(answer)?),
+        None => None,
+    };
+    let (verdict, message) = judge(
+        &execution,
+        limits,
+        checker,
+        &case.input,
+        expected.as_deref(),
+        &output,
+    )?;
+
+    Ok(Outcome {
+        case,
+        verdict,
+        message,
+        output,
+        usage: execution.usage,
+    })
+}
+
+/// Decide the verdict of a finished run whose stdout was `output`.
+///
+/// Returns no verdict when the run ended normally but there is nothing to compare against,
+/// and the checker's explanation alongside a wrong answer.
+pub fn judge(
+    execution: &Execution,
+    limits: &Limits,
+    checker: &dyn Checker,
+    input: &Path,
+    expected: Option<&str>,
+    output: &str,
+) -> io::Result<(Option<Verdict>, Option<String>)> {
+    if let Some(verdict) = Verdict::from_execution(execution, limits) {
+        return Ok((Some(verdict), None));
+    }
+    Ok(match expected {
+        Some(expected) => match checker.check(input, expected, output)? {
+            Ok(()) => (Some(Verdict::Accepted), None),
+            Err(reason) => (Some(Verdict::WrongAnswer), Some(reason)),
+        },
+        None => (None, None),
+    })
+}
+
+/// Print one row per test case and a final tally; returns whether every case passed.
+pub fn print_summary(outcomes: &[Outcome]) -> bool {
+    let width = outcomes
+        .iter()
+        .map(|outcome| outcome.case.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("case".len());
+    println!(
+        "{:<width$}  {:>8}  {:>9}  verdict",
+        "case",
+        "time",
+        "memory",
+        width = width
+    );
+    for outcome in outcomes {
+        let mut verdict = outcome
+            .verdict
+            .map_or("-".to_string(), |verdict| verdict.to_string());
+        if let Some(ref message) = outcome.message {
+            verdict = format!("{}: {}", verdict, message);
+        }
+        println!(
+            "{:<width$}  {:>5} ms  {:>6.1} MB  {}",
+            outcome.case.name,
+            outcome.usage.time.as_millis(),
+            outcome.usage.memory as f64 / (1 << 20) as f64,
+            verdict,
+            width = width
+        );
+    }
+
+    let (passed, judged) = tally(outcomes);
+    if judged < outcomes.len() {
+        println!(
+            "passed {}/{}, {} without an answer",
+            passed,
+            judged,
+            outcomes.len() - judged
+        );
+    } else {
+        println!("passed {}/{}", passed, judged);
+    }
+    passed == judged
+}
+
+/// Count the accepted cases and the cases that got a verdict at all.
+///
+/// Cases without an expected output only have their output shown, so they neither pass nor fail.
+fn tally(outcomes: &[Outcome]) -> (usize, usize) {
+    let judged = outcomes.iter().filter(|outcome| outcome.verdict.is_some());
+    let passed = judged
+        .clone()
+        .filter(|outcome| outcome.verdict == Some(Verdict::Accepted))
+        .count();
+    (passed, judged.count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover() {
+        let dir = std::env::temp_dir().join(format!("cp-rust-discover-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["10.in", "10.out", "2.in", "2.out", "3.in", "a.in", "a.out"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        let names: Vec<String> = discover(&dir)
+            .unwrap()
+            .into_iter()
+            .map(|case| case.name)
+            .collect();
+        assert_eq!(names, ["2", "10", "a"]);
+        assert_eq!(discover_inputs(&dir).unwrap().len(), 4);
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        let err = discover(&dir).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(
+            err.to_string(),
+            format!("no tests found: {} does not exist", dir.display())
+        );
+    }
+
+    #[test]
+    fn test_tally() {
+        let outcome = |verdict| Outcome {
+            case: TestCase {
+                name: "1".to_string(),
+                input: PathBuf::from("1.in"),
+                answer: None,
+            },
+            verdict,
+            message: None,
+            output: String::new(),
+            usage: Usage::default(),
+        };
+
+        let unjudged = [outcome(None)];
+        assert_eq!(tally(&unjudged), (0, 0));
+        assert!(print_summary(&unjudged));
+
+        let mixed = [outcome(Some(Verdict::Accepted)), outcome(None)];
+        assert_eq!(tally(&mixed), (1, 1));
+        assert!(print_summary(&mixed));
+
+        let failed = [outcome(Some(Verdict::WrongAnswer)), outcome(None)];
+        assert_eq!(tally(&failed), (0, 1));
+        assert!(!print_summary(&failed));
+    }
+
+    #[test]
+    fn test_limits() {
+        let dir = std::env::temp_dir().join(format!("cp-rust-limits-{}", std::process::id()));
+        assert_eq!(load_limits(&dir).unwrap(), None);
+
+        let limits = Limits::new(Duration::from_millis(1500), 512 << 20);
+        save_limits(&dir, &limits).unwrap();
+        assert_eq!(load_limits(&dir).unwrap(), Some(limits));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,371 @@
+//! Segment tree with lazy propagation, following the AtCoder Library API.
+use std::ops::RangeBounds;
+
+use crate::segtree::{bounds, Monoid};
+
+/// Maps acting on the elements of a monoid, closed under composition.
+pub trait MapMonoid {
+    type M: Monoid;
+    type F: Clone;
+
+    fn identity_element() -> <Self::M as Monoid>::S {
+        Self::M::identity()
+    }
+
+    fn binary_operation(
+        a: &<Self::M as Monoid>::S,
+        b: &<Self::M as Monoid>::S,
+    ) -> <Self::M as Monoid>::S {
+        Self::M::binary_operation(a, b)
+    }
+
+    fn identity_map() -> Self::F;
+
+    fn mapping(f: &Self::F, x: &<Self::M as Monoid>::S) -> <Self::M as Monoid>::S;
+
+    /// `f ∘ g`: apply `g` first, then `f`.
+    fn composition(f: &Self::F, g: &Self::F) -> Self::F;
+}
+
+/// Range updates and range folds in `O(log n)`.
+pub struct LazySegtree<F: MapMonoid> {
+    n: usize,
+    size: usize,
+    log: usize,
+    d: Vec<<F::M as Monoid>::S>,
+    lz: Vec<F::F>,
+}
+
+impl<F: MapMonoid> LazySegtree<F> {
+    pub fn new(n: usize) -> Self {
+        vec![F::identity_element(); n].into()
+    }
+
+    pub fn set(&mut self, p: usize, x: <F::M as Monoid>::S) {
+        assert!(p < self.n);
+        let p = p + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(p >> i);
+        }
+        self.d[p] = x;
+        for i in 1..=self.log {
+            self.update(p >> i);
+        }
+    }
+
+    pub fn get(&mut self, p: usize) -> <F::M as Monoid>::S {
+        assert!(p < self.n);
+        let p = p + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(p >> i);
+        }
+        self.d[p].clone()
+    }
+
+    /// Fold of the elements in `range`.
+    pub fn prod(&mut self, range: impl RangeBounds<usize>) -> <F::M as Monoid>::S {
+        let (mut l, mut r) = bounds(range, self.n);
+        if l == r {
+            return F::identity_element();
+        }
+        l += self.size;
+        r += self.size;
+        for i in (1..=self.log).rev() {
+            if ((l >> i) << i) != l {
+                self.push(l >> i);
+            }
+            if ((r >> i) << i) != r {
+                self.push(r >> i);
+            }
+        }
+        let mut sml = F::identity_element();
+        let mut smr = F::identity_element();
+        while l < r {
+            if l & 1 == 1 {
+                sml = F::binary_operation(&sml, &self.d[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                smr = F::binary_operation(&self.d[r], &smr);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        F::binary_operation(&sml, &smr)
+    }
+
+    pub fn all_prod(&self) -> <F::M as Monoid>::S {
+        self.d[1].clone()
+    }
+
+    /// Apply `f` to the element at `p`.
+    pub fn apply(&mut self, p: usize, f: F::F) {
+        assert!(p < self.n);
+        let p = p + self.size;
+        for i in (1..=self.log).rev() {
+            self.push(p >> i);
+        }
+        self.d[p] = F::mapping(&f, &self.d[p]);
+        for i in 1..=self.log {
+            self.update(p >> i);
+        }
+    }
+
+    /// Apply `f` to every element in `range`.
+    pub fn apply_range(&mut self, range: impl RangeBounds<usize>, f: F::F) {
+        let (mut l, mut r) = bounds(range, self.n);
+        if l == r {
+            return;
+        }
+        l += self.size;
+        r += self.size;
+        for i in (1..=self.log).rev() {
+            if ((l >> i) << i) != l {
+                self.push(l >> i);
+            }
+            if ((r >> i) << i) != r {
+                self.push((r - 1) >> i);
+            }
+        }
+        let (mut l2, mut r2) = (l, r);
+        while l2 < r2 {
+            if l2 & 1 == 1 {
+                self.all_apply(l2, f.clone());
+                l2 += 1;
+            }
+            if r2 & 1 == 1 {
+                r2 -= 1;
+                self.all_apply(r2, f.clone());
+            }
+            l2 >>= 1;
+            r2 >>= 1;
+        }
+        for i in 1..=self.log {
+            if ((l >> i) << i) != l {
+                self.update(l >> i);
+            }
+            if ((r >> i) << i) != r {
+                self.update((r - 1) >> i);
+            }
+        }
+    }
+
+    /// The largest `r` such that `g(prod(l..r))` holds, assuming `g` is monotone and
+    /// `g(identity)` holds.
+    pub fn max_right<G>(&mut self, mut l: usize, g: G) -> usize
+    where
+        G: Fn(&<F::M as Monoid>::S) -> bool,
+    {
+        assert!(l <= self.n);
+        assert!(g(&F::identity_element()));
+        if l == self.n {
+            return self.n;
+        }
+        l += self.size;
+        for i in (1..=self.log).rev() {
+            self.push(l >> i);
+        }
+        let mut sm = F::identity_element();
+        loop {
+            while l % 2 == 0 {
+                l >>= 1;
+            }
+            if !g(&F::binary_operation(&sm, &self.d[l])) {
+                while l < self.size {
+                    self.push(l);
+                    l *= 2;
+                    let res = F::binary_operation(&sm, &self.d[l]);
+                    if g(&res) {
+                        sm = res;
+                        l += 1;
+                    }
+                }
+                return l - self.size;
+            }
+            sm = F::binary_operation(&sm, &self.d[l]);
+            l += 1;
+            if l & l.wrapping_neg() == l {
+                return self.n;
+            }
+        }
+    }
+
+    /// The smallest `l` such that `g(prod(l..r))` holds, assuming `g` is monotone and
+    /// `g(identity)` holds.
+    pub fn min_left<G>(&mut self, mut r: usize, g: G) -> usize
+    where
+        G: Fn(&<F::M as Monoid>::S) -> bool,
+    {
+        assert!(r <= self.n);
+        assert!(g(&F::identity_element()));
+        if r == 0 {
+            return 0;
+        }
+        r += self.size;
+        for i in (1..=self.log).rev() {
+            self.push((r - 1) >> i);
+        }
+        let mut sm = F::identity_element();
+        loop {
+            r -= 1;
+            while r > 1 && r % 2 == 1 {
+                r >>= 1;
+            }
+            if !g(&F::binary_operation(&self.d[r], &sm)) {
+                while r < self.size {
+                    self.push(r);
+                    r = 2 * r + 1;
+                    let res = F::binary_operation(&self.d[r], &sm);
+                    if g(&res) {
+                        sm = res;
+                        r -= 1;
+                    }
+                }
+                return r + 1 - self.size;
+            }
+            sm = F::binary_operation(&self.d[r], &sm);
+            if r & r.wrapping_neg() == r {
+                return 0;
+            }
+        }
+    }
+
+    fn update(&mut self, k: usize) {
+        self.d[k] = F::binary_operation(&self.d[2 * k], &self.d[2 * k + 1]);
+    }
+
+    fn all_apply(&mut self, k: usize, f: F::F) {
+        self.d[k] = F::mapping(&f, &self.d[k]);
+        if k < self.size {
+            self.lz[k] = F::composition(&f, &self.lz[k]);
+        }
+    }
+
+    fn push(&mut self, k: usize) {
+        let f = std::mem::replace(&mut self.lz[k], F::identity_map());
+        self.all_apply(2 * k, f.clone());
+        self.all_apply(2 * k + 1, f);
+    }
+}
+
+impl<F: MapMonoid> From<Vec<<F::M as Monoid>::S>> for LazySegtree<F> {
+    fn from(v: Vec<<F::M as Monoid>::S>) -> Self {
+        let n = v.len();
+        let size = n.next_power_of_two();
+        let log = size.trailing_zeros() as usize;
+        let mut d = vec![F::identity_element(); 2 * size];
+        d[size..size + n].clone_from_slice(&v);
+        let lz = vec![F::identity_map(); size];
+        let mut tree = LazySegtree {
+            n,
+            size,
+            log,
+            d,
+            lz,
+        };
+        for i in (1..size).rev() {
+            tree.update(i);
+        }
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sum and length of a range, so that adding to every element can update the sum.
+    struct SumLen;
+
+    impl Monoid for SumLen {
+        type S = (i64, usize);
+
+        fn identity() -> (i64, usize) {
+            (0, 0)
+        }
+
+        fn binary_operation(a: &(i64, usize), b: &(i64, usize)) -> (i64, usize) {
+            (a.0 + b.0, a.1 + b.1)
+        }
+    }
+
+    /// `x -> a * x + b` on every element.
+    struct Affine;
+
+    impl MapMonoid for Affine {
+        type M = SumLen;
+        type F = (i64, i64);
+
+        fn identity_map() -> (i64, i64) {
+            (1, 0)
+        }
+
+        fn mapping(&(a, b): &(i64, i64), &(sum, len): &(i64, usize)) -> (i64, usize) {
+            (a * sum + b * len as i64, len)
+        }
+
+        fn composition(&(a, b): &(i64, i64), &(c, d): &(i64, i64)) -> (i64, i64) {
+            (a * c, a * d + b)
+        }
+    }
+
+    fn tree(a: &[i64]) -> LazySegtree<Affine> {
+        a.iter().map(|&x| (x, 1)).collect::<Vec<_>>().into()
+    }
+
+    #[test]
+    fn test_lazy_segtree() {
+        let mut a = vec![3, 1, 4, 1, 5, 9, 2];
+        let mut tree = tree(&a);
+        let operations = [
+            (1, 5, 2, 1),
+            (0, 7, 1, -3),
+            (3, 4, -1, 0),
+            (2, 2, 5, 5),
+            (4, 7, 3, 2),
+        ];
+        for (l, r, mul, add) in operations {
+            tree.apply_range(l..r, (mul, add));
+            for x in &mut a[l..r] {
+                *x = mul * *x + add;
+            }
+            for l in 0..=a.len() {
+                for r in l..=a.len() {
+                    assert_eq!(tree.prod(l..r).0, a[l..r].iter().sum::<i64>());
+                }
+            }
+        }
+        tree.apply(0, (0, 100));
+        a[0] = 100;
+        tree.set(6, (-7, 1));
+        a[6] = -7;
+        assert_eq!(tree.get(0), (100, 1));
+        assert_eq!(tree.all_prod().0, a.iter().sum::<i64>());
+    }
+
+    #[test]
+    fn test_binary_search() {
+        let mut a = vec![3, 1, 4, 1, 5, 9, 2];
+        let mut tree = tree(&a);
+        tree.apply_range(2..5, (2, 1));
+        for x in &mut a[2..5] {
+            *x = 2 * *x + 1;
+        }
+        for limit in 0..=40 {
+            for l in 0..=a.len() {
+                let expected = (l..=a.len())
+                    .rev()
+                    .find(|&r| a[l..r].iter().sum::<i64>() <= limit)
+                    .unwrap();
+                assert_eq!(tree.max_right(l, |&(s, _)| s <= limit), expected);
+            }
+            for r in 0..=a.len() {
+                let expected = (0..=r)
+                    .find(|&l| a[l..r].iter().sum::<i64>() <= limit)
+                    .unwrap();
+                assert_eq!(tree.min_left(r, |&(s, _)| s <= limit), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,353 @@
+/// Kind of a token of Rust source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Ident,
+    /// A lifetime or a loop label, such as `'a`.
+    Lifetime,
+    /// A string, character or number literal.
+    Literal,
+    /// A single punctuation character; `::` is two tokens.
+    Punct,
+    Comment,
+    Whitespace,
+}
+
+/// A token with its text; concatenating the text of all tokens gives back the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: Kind,
+    pub text: &'a str,
+}
+
+impl Token<'_> {
+    /// Whether the token is whitespace or a comment.
+    pub fn is_trivia(&self) -> bool {
+        matches!(self.kind, Kind::Whitespace | Kind::Comment)
+    }
+
+    pub fn is_ident(&self, ident: &str) -> bool {
+        self.kind == Kind::Ident && self.text == ident
+    }
+
+    pub fn is_punct(&self, c: char) -> bool {
+        self.kind == Kind::Punct && self.text.starts_with(c)
+    }
+}
+
+/// Split Rust source into tokens, just precisely enough to tell code from strings and comments.
+///
+/// Unterminated literals and comments extend to the end of the source.
+pub fn tokenize(src: &str) -> Vec<Token<'_>> {
+    let bytes = src.as_bytes();
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    while start < bytes.len() {
+        let (kind, end) = lex(src, start);
+        tokens.push(Token {
+            kind,
+            text: &src[start..end],
+        });
+        start = end;
+    }
+    tokens
+}
+
+/// Lex one token starting at `start`, returning its kind and end.
+fn lex(src: &str, start: usize) -> (Kind, usize) {
+    let bytes = src.as_bytes();
+    let at = |i: usize| bytes.get(i).copied().unwrap_or(0);
+    let c = src[start..]
+        .chars()
+        .next()
+        .expect("start is within the source");
+
+    if c.is_whitespace() {
+        let len = src[start..]
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(src.len() - start);
+        return (Kind::Whitespace, start + len);
+    }
+    if c == '/' && at(start + 1) == b'/' {
+        let len = src[start..].find('\n').unwrap_or(src.len() - start);
+        return (Kind::Comment, start + len);
+    }
+    if c == '/' && at(start + 1) == b'*' {
+        let mut depth = 0;
+        let mut i = start;
+        while i < bytes.len() {
+            if at(i) == b'/' && at(i + 1) == b'*' {
+                depth += 1;
+                i += 2;
+            } else if at(i) == b'*' && at(i + 1) == b'/' {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    break;
+                }
+            } else {
+                i += 1;
+            }
+        }
+        return (Kind::Comment, i.min(bytes.len()));
+    }
+    if c == '"' {
+        return (Kind::Literal, quoted(bytes, start + 1, b'"'));
+    }
+    if c == '\'' {
+        // `'a'` and `'\n'` are characters, `'a` is a lifetime.
+        if at(start + 1) == b'\\' {
+            return (Kind::Literal, quoted(bytes, start + 1, b'\''));
+        }
+        let next = src[start + 1..].chars().next().map_or(0, char::len_utf8);
+        if next > 0 && at(start + 1 + next) == b'\'' {
+            return (Kind::Literal, start + 2 + next);
+        }
+        return (Kind::Lifetime, ident_end(src, start + 1));
+    }
+    if c.is_ascii_digit() {
+        let mut i = start;
+        while i < bytes.len() {
+            let b = at(i);
+            let exponent = (b == b'+' || b == b'-') && matches!(at(i - 1), b'e' | b'E');
+            let fraction = b == b'.' && at(i + 1).is_ascii_digit();
+            if b.is_ascii_alphanumeric() || b == b'_' || fraction || (exponent && !hex(src, start))
+            {
+                i += 1;
+            } else {
+                break;
+            }
+        }
+        return (Kind::Literal, i);
+    }
+    if c == '_' || c.is_alphabetic() {
+        let end = ident_end(src, start);
+        let prefix = &src[start..end];
+        // Raw strings, byte strings and raw identifiers.
+        if matches!(prefix, "r" | "br" | "cr") && matches!(at(end), b'"' | b'#') {
+            let hashes = src[end..].bytes().take_while(|&b| b == b'#').count();
+            if at(end + hashes) == b'"' {
+                let close = format!("\"{}", "#".repeat(hashes));
+                let body = end + hashes + 1;
+                let len = src[body..]
+                    .find(&close)
+                    .map_or(src.len() - body, |len| len + close.len());
+                return (Kind::Literal, body + len);
+            }
+            if prefix == "r" && hashes == 1 {
+                return (Kind::Ident, ident_end(src, end + 1));
+            }
+        }
+        if matches!(prefix, "b" | "c") && at(end) == b'"' {
+            return (Kind::Literal, quoted(bytes, end + 1, b'"'));
+        }
+        if prefix == "b" && at(end) == b'\'' {
+            return (Kind::Literal, quoted(bytes, end + 1, b'\''));
+        }
+        return (Kind::Ident, end);
+    }
+    (Kind::Punct, start + c.len_utf8())
+}
+
+fn ident_end(src: &str, start: usize) -> usize {
+    start
+        + src[start..]
+            .find(|c: char| !(c == '_' || c.is_alphanumeric()))
+            .unwrap_or(src.len() - start)
+}
+
+/// End of a literal closed by `quote`, starting right after the opening quote.
+fn quoted(bytes: &[u8], mut i: usize, quote: u8) -> usize {
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b if b == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+fn hex(src: &str, start: usize) -> bool {
+    src[start..].starts_with("0x") || src[start..].starts_with("0X")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(src: &str) -> Vec<(Kind, &str)> {
+        tokenize(src)
+            .into_iter()
+            .filter(|token| token.kind != Kind::Whitespace)
+            .map(|token| (token.kind, token.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let src = r##"fn f<'a>(x: &'a str) -> char { /* a /* nested */ "comment" */ 'x' } // crate::x
+let s = r#"crate::"x""#; let b = b'\''; let n = 1e-9 + 0x1f - 1..2;"##;
+        assert_eq!(
+            tokenize(src).iter().map(|t| t.text).collect::<String>(),
+            src
+        );
+        assert_eq!(
+            kinds(src),
+            [
+                (Kind::Ident, "fn"),
+                (Kind::Ident, "f"),
+                (Kind::Punct, "<"),
+                (Kind::Lifetime, "'a"),
+                (Kind::Punct, ">"),
+                (Kind::Punct, "("),
+                (Kind::Ident, "x"),
+                (Kind::Punct, ":"),
+                (Kind::Punct, "&"),
+                (Kind::Lifetime, "'a"),
+                (Kind::Ident, "str"),
+                (Kind::Punct, ")"),
+                (Kind::Punct, "-"),
+                (Kind::Punct, ">"),
+                (Kind::Ident, "char"),
+                (Kind::Punct, "{"),
+                (Kind::Comment, "/* a /* nested */ \"comment\" */"),
+                (Kind::Literal, "'x'"),
+                (Kind::Punct, "}"),
+                (Kind::Comment, "// crate::x"),
+                (Kind::Ident, "let"),
+                (Kind::Ident, "s"),
+                (Kind::Punct, "="),
+                (Kind::Literal, "r#\"crate::\"x\"\"#"),
+                (Kind::Punct, ";"),
+                (Kind::Ident, "let"),
+                (Kind::Ident, "b"),
+                (Kind::Punct, "="),
+                (Kind::Literal, "b'\\''"),
+                (Kind::Punct, ";"),
+                (Kind::Ident, "let"),
+                (Kind::Ident, "n"),
+                (Kind::Punct, "="),
+                (Kind::Literal, "1e-9"),
+                (Kind::Punct, "+"),
+                (Kind::Literal, "0x1f"),
+                (Kind::Punct, "-"),
+                (Kind::Literal, "1"),
+                (Kind::Punct, "."),
+                (Kind::Punct, "."),
+                (Kind::Literal, "2"),
+                (Kind::Punct, ";"),
+            ]
+        );
+    }
+
+    /// Tokens other than whitespace, checking that they give back the source.
+    fn lossless_kinds(src: &str) -> Vec<(Kind, &str)> {
+        assert_eq!(
+            tokenize(src).iter().map(|t| t.text).collect::<String>(),
+            src
+        );
+        kinds(src)
+    }
+
+    #[test]
+    fn test_raw_strings() {
+        let src = r####"r"a\" r#"b"# r##"c"#"d"## br#"e"# cr"f" r"// x" r#"/* y"# r#type r"g""####;
+        assert_eq!(
+            lossless_kinds(src),
+            [
+                (Kind::Literal, r#"r"a\""#),
+                (Kind::Literal, r##"r#"b"#"##),
+                (Kind::Literal, r###"r##"c"#"d"##"###),
+                (Kind::Literal, r##"br#"e"#"##),
+                (Kind::Literal, r#"cr"f""#),
+                (Kind::Literal, r#"r"// x""#),
+                (Kind::Literal, r##"r#"/* y"#"##),
+                (Kind::Ident, "r#type"),
+                (Kind::Literal, r#"r"g""#),
+            ]
+        );
+        // An unterminated raw string runs to the end.
+        assert_eq!(
+            lossless_kinds("r#\"never closed\" x"),
+            [(Kind::Literal, "r#\"never closed\" x")]
+        );
+    }
+
+    #[test]
+    fn test_chars_and_lifetimes() {
+        let src = r"'a' '\'' '\\' '\u{1F600}' 'é' b'x' b'\\' b'\'' 'a 'static 'é2 'outer: loop {} f::<'a>('b')";
+        assert_eq!(
+            lossless_kinds(src),
+            [
+                (Kind::Literal, "'a'"),
+                (Kind::Literal, r"'\''"),
+                (Kind::Literal, r"'\\'"),
+                (Kind::Literal, r"'\u{1F600}'"),
+                (Kind::Literal, "'é'"),
+                (Kind::Literal, "b'x'"),
+                (Kind::Literal, r"b'\\'"),
+                (Kind::Literal, r"b'\''"),
+                (Kind::Lifetime, "'a"),
+                (Kind::Lifetime, "'static"),
+                (Kind::Lifetime, "'é2"),
+                (Kind::Lifetime, "'outer"),
+                (Kind::Punct, ":"),
+                (Kind::Ident, "loop"),
+                (Kind::Punct, "{"),
+                (Kind::Punct, "}"),
+                (Kind::Ident, "f"),
+                (Kind::Punct, ":"),
+                (Kind::Punct, ":"),
+                (Kind::Punct, "<"),
+                (Kind::Lifetime, "'a"),
+                (Kind::Punct, ">"),
+                (Kind::Punct, "("),
+                (Kind::Literal, "'b'"),
+                (Kind::Punct, ")"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strings() {
+        let src = r#""a\"b // c" b"\x00\"" c"d" "/* e */" "multi
+line""#;
+        assert_eq!(
+            lossless_kinds(src),
+            [
+                (Kind::Literal, r#""a\"b // c""#),
+                (Kind::Literal, r#"b"\x00\"""#),
+                (Kind::Literal, r#"c"d""#),
+                (Kind::Literal, r#""/* e */""#),
+                (Kind::Literal, "\"multi\nline\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        let src = "/// doc\n//! inner doc\n/** block doc */ /*! inner */\n\
+                   /* 1 /* 2 /* 3 */ 2 */ 1 */ x /**/ y /* \"*/\" */ z // trailing";
+        assert_eq!(
+            lossless_kinds(src),
+            [
+                (Kind::Comment, "/// doc"),
+                (Kind::Comment, "//! inner doc"),
+                (Kind::Comment, "/** block doc */"),
+                (Kind::Comment, "/*! inner */"),
+                (Kind::Comment, "/* 1 /* 2 /* 3 */ 2 */ 1 */"),
+                (Kind::Ident, "x"),
+                (Kind::Comment, "/**/"),
+                (Kind::Ident, "y"),
+                // Quotes do not protect `*/` inside a comment.
+                (Kind::Comment, "/* \"*/"),
+                (Kind::Literal, "\" */ z // trailing"),
+            ]
+        );
+        // An unterminated block comment runs to the end.
+        assert_eq!(
+            lossless_kinds("a /* b /* c */ d"),
+            [(Kind::Ident, "a"), (Kind::Comment, "/* b /* c */ d")]
+        );
+    }
+}
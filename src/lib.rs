@@ -1,2 +1,34 @@
+pub mod build;
+pub mod bundle;
+pub mod checker;
 pub mod codeforces;
+pub mod combinatorics;
+pub mod diff;
+pub mod dsu;
+pub mod fenwick;
+pub mod graph;
+pub mod input;
+pub mod interactive;
+pub mod judge;
+pub mod lazy_segtree;
+pub mod lexer;
+pub mod modint;
+pub mod num;
+pub mod number_theory;
+pub mod output;
+pub mod prefix_sum;
+pub mod prune;
+pub mod runner;
+pub mod segtree;
+pub mod shortest_path;
+pub mod shrink;
+pub mod sparse_table;
+pub mod statement;
+pub mod stress;
+pub mod string;
+pub mod template;
+#[cfg(all(test, unix))]
+mod test_util;
+pub(crate) mod util;
+pub mod watch;
 pub mod webdriver;
@@ -1,15 +1,28 @@
-use std::fs::{copy as fcopy, File};
-use std::io::{copy, Error};
-use std::path::Path;
-use std::process::{Command, Stdio};
+use std::collections::BTreeMap;
+use std::error;
+use std::fs;
+use std::io::{Error, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use cp_rust::build;
+use cp_rust::bundle::{self, Library};
+use cp_rust::checker::{self, Checker};
+use cp_rust::diff::{self, DiffOptions};
+use cp_rust::interactive::Interaction;
+use cp_rust::judge::{self, Outcome, TestCase, Verdict};
+use cp_rust::runner::Limits;
+use cp_rust::shrink::Granularity;
+use cp_rust::statement;
+use cp_rust::stress::Stress;
+use cp_rust::template;
+use cp_rust::watch::{self, Watcher};
+use cp_rust::{codeforces, webdriver};
 use dotenv::dotenv;
 use git2::{Config, ObjectType, Repository, Signature};
 
-mod codeforces;
-mod webdriver;
-
 /// Handy commands for competitive programming in rust.
 #[derive(Parser)]
 #[command(version = "0.1.0", author = "Zihua Wu <wuzihua@pku.edu.cn>")]
@@ -18,26 +31,280 @@ struct Opts {
     subcmd: SubCommand,
 }
 
+/// Limits and output checking shared by `exec` and `stress`.
+#[derive(Args)]
+struct JudgeOpts {
+    /// CPU time limit in milliseconds, the problem's limit or 2000 by default.
+    #[clap(long)]
+    time_limit: Option<u64>,
+    /// Wall clock limit in milliseconds, twice the time limit by default.
+    #[clap(long)]
+    wall_time_limit: Option<u64>,
+    /// Memory limit in megabytes, the problem's limit or 256 by default.
+    #[clap(long)]
+    memory_limit: Option<u64>,
+    /// Output checker: exact, lines, tokens, icase, float[:EPS] or a checker bin name.
+    #[clap(short = 'c', long, default_value = "tokens")]
+    checker: String,
+    /// Show wrong answers as expected and actual output side by side.
+    #[clap(short = 'y', long)]
+    side_by_side: bool,
+}
+
+impl JudgeOpts {
+    /// Limits given on the command line, falling back to those recorded for `file_name` by `new`.
+    fn limits(&self, file_name: &str) -> Limits {
+        let recorded = judge::load_limits(&judge::test_dir(file_name))
+            .expect("failed to read limits")
+            .unwrap_or_default();
+        let mut limits = Limits::new(
+            self.time_limit.map_or(recorded.time, Duration::from_millis),
+            self.memory_limit
+                .map_or(recorded.memory, |memory_limit| memory_limit << 20),
+        );
+        if let Some(wall_time_limit) = self.wall_time_limit {
+            limits.wall_time = Duration::from_millis(wall_time_limit);
+        }
+        limits
+    }
+
+    fn checker(&self) -> Box<dyn Checker> {
+        checker::from_spec(&self.checker).unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            process::exit(1);
+        })
+    }
+
+    /// Colors only go to a terminal, whose width is taken from `COLUMNS` when set.
+    fn diff_options(&self) -> DiffOptions {
+        let mut options = DiffOptions {
+            side_by_side: self.side_by_side,
+            color: std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            ..DiffOptions::default()
+        };
+        if let Some(width) = std::env::var("COLUMNS").ok().and_then(|w| w.parse().ok()) {
+            options.width = width;
+        }
+        options
+    }
+}
+
+/// What `bundle` and `submit` leave out of the bundled library code.
+#[derive(Args)]
+struct BundleOpts {
+    /// Keep library items the program does not use.
+    #[clap(long)]
+    keep_unused: bool,
+    /// Remove comments from library code.
+    #[clap(long)]
+    strip_comments: bool,
+    /// Remove `#[cfg(test)]` items from library code.
+    #[clap(long)]
+    strip_tests: bool,
+}
+
+/// Build `src/bin/<file_name>.rs`, exiting with the compiler errors on failure.
+fn build_or_exit(file_name: &str) -> PathBuf {
+    build::build(file_name).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(1);
+    })
+}
+
+/// Build a program and run it against its tests, or a single input file.
+fn exec(
+    file_name: &str,
+    test_file: Option<&str>,
+    interactor: Option<&str>,
+    query_limit: Option<usize>,
+    judge: &JudgeOpts,
+) -> Result<Vec<Outcome>, Box<dyn error::Error>> {
+    let limits = judge.limits(file_name);
+    let binary = build::build(file_name)?;
+    let dir = judge::test_dir(file_name);
+
+    let mut outcomes = Vec::new();
+    if let Some(interactor) = interactor {
+        let interaction = Interaction {
+            solution: binary,
+            interactor: build::build(interactor)?,
+            limits,
+            query_limit,
+        };
+        let cases = match test_file {
+            Some(test_file) => vec![TestCase::from_input(Path::new(test_file))],
+            None => judge::discover_inputs(&dir)?,
+        };
+
+        for case in cases {
+            let outcome = interaction.run(case)?;
+            fs::write(outcome.case.input.with_extension("log"), &outcome.output)?;
+            outcomes.push(outcome);
+        }
+        println!("transcripts are saved next to the inputs as *.log");
+    } else {
+        let checker = checker::from_spec(&judge.checker)?;
+        let cases = match test_file {
+            Some(test_file) => vec![TestCase::from_input(Path::new(test_file))],
+            None => judge::discover(&dir)?,
+        };
+
+        for case in cases {
+            let outcome = judge::run(&binary, case, &limits, checker.as_ref())?;
+            if outcome.verdict.is_none() {
+                println!("{}", outcome.output);
+            }
+            outcomes.push(outcome);
+        }
+    }
+    Ok(outcomes)
+}
+
+/// The expected output of a wrong answer, if the case has one.
+fn wrong_answer_expected(outcome: &Outcome) -> Option<String> {
+    if outcome.verdict != Some(Verdict::WrongAnswer) {
+        return None;
+    }
+    let answer = outcome.case.answer.as_ref()?;
+    Some(fs::read_to_string(answer).unwrap_or_default())
+}
+
+/// Show how the output of wrong answers differs from the expected output.
+fn print_diffs(outcomes: &[Outcome], options: &DiffOptions) {
+    for outcome in outcomes {
+        if let Some(expected) = wrong_answer_expected(outcome) {
+            print_diff(&outcome.case.name, &expected, &outcome.output, options);
+        }
+    }
+}
+
+/// Show only where the first wrong answer starts to differ, for the compact watch view.
+fn print_first_diff(outcomes: &[Outcome]) {
+    let first = outcomes
+        .iter()
+        .find_map(|outcome| Some((outcome, wrong_answer_expected(outcome)?)));
+    let Some((outcome, expected)) = first else {
+        return;
+    };
+    if let Some(difference) = diff::first_difference(&expected, &outcome.output) {
+        println!();
+        println!("case {}, {}", outcome.case.name, difference);
+    }
+}
+
+fn print_diff(name: &str, expected: &str, actual: &str, options: &DiffOptions) {
+    if let Some(difference) = diff::first_difference(expected, actual) {
+        println!();
+        println!("case {}, {}", name, difference);
+        print!("{}", diff::render(expected, actual, options));
+    }
+}
+
+/// Bundle `src/bin/<file_name>.rs` with the library modules it uses into a single file,
+/// exiting on failure.
+fn bundle_or_exit(file_name: &str, opts: &BundleOpts) -> String {
+    let options = bundle::Options {
+        keep_unused: opts.keep_unused,
+        strip_comments: opts.strip_comments,
+        strip_tests: opts.strip_tests,
+    };
+    fs::read_to_string(format!("src/bin/{}.rs", file_name))
+        .and_then(|solution| bundle::bundle(&solution, &Library::open(Path::new("src"))?, &options))
+        .unwrap_or_else(|err| {
+            eprintln!("failed to bundle {}: {}", file_name, err);
+            process::exit(1);
+        })
+}
+
+/// Save the samples and limits of a problem page as its tests, returning the number of samples.
+fn save_statement(file_name: &str, html: &str) -> Result<usize, Error> {
+    let dir = judge::test_dir(file_name);
+    let samples = statement::samples(html);
+    statement::save(&dir, &samples)?;
+    if let Some(limits) = statement::limits(html) {
+        judge::save_limits(&dir, &limits)?;
+    }
+    Ok(samples.len())
+}
+
+/// Author for templates: `CP_AUTHOR` if set, otherwise `user.name` from git.
+fn author() -> String {
+    std::env::var("CP_AUTHOR").unwrap_or_else(|_| {
+        Repository::open(".")
+            .and_then(|repo| repo.config())
+            .or_else(|_| Config::open_default())
+            .and_then(|cfg| cfg.get_string("user.name"))
+            .unwrap_or_default()
+    })
+}
+
 #[derive(Subcommand)]
 enum SubCommand {
-    /// Create a new source file from a template file.
+    /// Create a new source file from a template file, fetching the samples of Codeforces problems.
+    ///
+    /// Templates may use `{{id}}`, `{{name}}`, `{{url}}`, `{{date}}` and `{{author}}`.
     #[clap(name = "new")]
     New {
         /// Names of the files to be created.
+        #[clap(required_unless_present = "contest")]
         file_names: Vec<String>,
+        /// Create every problem of a Codeforces contest, e.g. `1361a` to `1361f`.
+        #[clap(long)]
+        contest: Option<u32>,
         /// Select template to use.
         #[clap(short = 't', default_value = "default")]
         template: String,
+        /// Overwrite existing source files.
+        #[clap(short = 'f', long)]
+        force: bool,
     },
 
-    /// Execute a program with an input file.
+    /// Run a program against all sample tests in `tests/<file_name>`.
     #[clap(name = "exec")]
     Exec {
         /// Name of the file to be executed.
         file_name: String,
-        /// Select test input.
-        #[clap(short = 't', default_value = "test.in")]
-        test_file: String,
+        /// Run a single input file instead of the sample tests.
+        #[clap(short = 't')]
+        test_file: Option<String>,
+        /// Name of an interactor, called as `<interactor> <input> [<answer>]`.
+        #[clap(short = 'i', long)]
+        interactor: Option<String>,
+        /// Maximum number of lines the program may send to the interactor.
+        #[clap(long, requires = "interactor")]
+        query_limit: Option<usize>,
+        /// Rebuild and rerun whenever the source file, the library or the tests change.
+        #[clap(short = 'w', long)]
+        watch: bool,
+        #[command(flatten)]
+        judge: JudgeOpts,
+    },
+
+    /// Stress test a program against a brute force on generated inputs.
+    #[clap(name = "stress")]
+    Stress {
+        /// Name of the file to be tested.
+        file_name: String,
+        /// Name of the brute force solution.
+        #[clap(short = 'b', long)]
+        brute: String,
+        /// Name of the generator, called as `<generator> <seed> [<size>]`.
+        #[clap(short = 'g', long)]
+        generator: String,
+        /// Size parameter for the generator; failing inputs are shrunk by lowering it.
+        #[clap(long)]
+        size: Option<u64>,
+        /// Shrink failing inputs further by removing `lines` or `tokens`.
+        #[clap(long)]
+        shrink: Option<Granularity>,
+        /// First seed passed to the generator.
+        #[clap(short = 's', long, default_value = "1")]
+        seed: u64,
+        /// Number of seeds to try, unlimited by default.
+        #[clap(short = 'n', long)]
+        iterations: Option<u64>,
+        #[command(flatten)]
+        judge: JudgeOpts,
     },
 
     /// Add and commit a source file.
@@ -47,11 +314,25 @@ enum SubCommand {
         file_name: String,
     },
 
-    /// Submit to Codeforces
+    /// Bundle a source file with the library modules it uses into one self-contained file.
+    #[clap(name = "bundle")]
+    Bundle {
+        /// Name of the file to be bundled.
+        file_name: String,
+        /// Write the bundle to this file instead of stdout.
+        #[clap(short = 'o', long)]
+        output: Option<PathBuf>,
+        #[command(flatten)]
+        bundle: BundleOpts,
+    },
+
+    /// Submit to Codeforces, bundling the library modules the file uses.
     #[clap(name = "submit")]
     Submit {
         /// Name of the file to be submitted.
         file_name: String,
+        #[command(flatten)]
+        bundle: BundleOpts,
     },
 
     /// Query Codeforces problems.
@@ -91,39 +372,193 @@ async fn main() -> Result<(), Error> {
         SubCommand::New {
             file_names,
             template,
+            contest,
+            force,
         } => {
-            for file_name in file_names {
-                fcopy(
-                    format!("src/templates/{}.rs", template),
-                    format!("src/bin/{}.rs", file_name),
-                )
-                .expect("failed to create file");
+            // Problem names from the API, for when a statement cannot be fetched.
+            let mut problems: Vec<(String, Option<String>)> =
+                file_names.into_iter().map(|name| (name, None)).collect();
+            if let Some(contest_id) = contest {
+                let contest_problems = codeforces::get_contest_problems(contest_id)
+                    .await
+                    .expect("failed to query contest.standings");
+                problems.extend(contest_problems.into_iter().map(|problem| {
+                    let file_name = format!("{}{}", contest_id, problem.index.to_lowercase());
+                    (file_name, Some(problem.name))
+                }));
+            }
+
+            let source = fs::read_to_string(format!("src/templates/{}.rs", template))
+                .expect("failed to read template");
+            let author = author();
+            let date = template::today();
+            let mut skipped = false;
+            for (file_name, name) in problems {
+                let path = PathBuf::from(format!("src/bin/{}.rs", file_name));
+                if path.exists() && !force {
+                    eprintln!(
+                        "{} already exists, pass --force to overwrite it",
+                        path.display()
+                    );
+                    skipped = true;
+                    continue;
+                }
+
+                let problem = codeforces::parse_problem_id(&file_name);
+                let url = problem
+                    .as_ref()
+                    .map(|(contest_id, index)| codeforces::problem_url(*contest_id, index));
+                let mut html = None;
+                if let Some((contest_id, index)) = problem {
+                    match codeforces::get_problem_page(contest_id, &index).await {
+                        Ok(page) => html = Some(page),
+                        Err(err) => eprintln!("failed to fetch {}: {}", file_name, err),
+                    }
+                }
+
+                let vars = BTreeMap::from([
+                    ("id", file_name.clone()),
+                    (
+                        "name",
+                        html.as_deref()
+                            .and_then(statement::title)
+                            .or(name)
+                            .unwrap_or_default(),
+                    ),
+                    ("url", url.unwrap_or_default()),
+                    ("date", date.clone()),
+                    ("author", author.clone()),
+                ]);
+                fs::write(&path, template::render(&source, &vars)).expect("failed to create file");
+
+                if let Some(html) = html {
+                    match save_statement(&file_name, &html).expect("failed to save samples") {
+                        0 => eprintln!("no samples found for {}", file_name),
+                        count => println!("saved {} samples for {}", count, file_name),
+                    }
+                }
+            }
+            if skipped {
+                process::exit(1);
             }
         }
 
         SubCommand::Exec {
             file_name,
             test_file,
+            interactor,
+            query_limit,
+            watch,
+            judge,
         } => {
-            let mut child = Command::new("sh")
-                .arg("-c")
-                .arg(format!("cargo run --bin {}", file_name))
-                .stdin(Stdio::piped())
-                .stdout(Stdio::piped())
-                .spawn()
-                .expect("failed to execute file");
-
-            let mut file = File::open(test_file)
-                .ok()
-                .expect("failed to open input file");
-            copy(&mut file, child.stdin.as_mut().unwrap()).expect("failed to copy input");
-            let output = child.wait_with_output().unwrap();
-            let output = String::from_utf8(output.stdout).unwrap();
-            println!("{}", output);
+            let exec = || {
+                exec(
+                    &file_name,
+                    test_file.as_deref(),
+                    interactor.as_deref(),
+                    query_limit,
+                    &judge,
+                )
+            };
+
+            if !watch {
+                let outcomes = exec().unwrap_or_else(|err| {
+                    eprintln!("{}", err);
+                    process::exit(1);
+                });
+                let passed = judge::print_summary(&outcomes);
+                if interactor.is_none() {
+                    print_diffs(&outcomes, &judge.diff_options());
+                }
+                if !passed {
+                    process::exit(1);
+                }
+                return Ok(());
+            }
+
+            // Solutions may use any library module, so `src/*.rs` is watched as well.
+            let mut watcher = Watcher::new(vec![
+                PathBuf::from(format!("src/bin/{}.rs", file_name)),
+                PathBuf::from("src"),
+                judge::test_dir(&file_name),
+            ]);
+            loop {
+                watch::clear_screen()?;
+                println!("watching {} for changes, press Ctrl-C to stop", file_name);
+                match exec() {
+                    Ok(outcomes) => {
+                        judge::print_summary(&outcomes);
+                        if interactor.is_none() {
+                            print_first_diff(&outcomes);
+                        }
+                    }
+                    Err(err) => eprintln!("{}", err),
+                }
+                watcher.wait();
+            }
+        }
+
+        SubCommand::Stress {
+            file_name,
+            brute,
+            generator,
+            size,
+            shrink,
+            seed,
+            iterations,
+            judge,
+        } => {
+            let checker = judge.checker();
+            let stress = Stress {
+                solution: build_or_exit(&file_name),
+                brute: build_or_exit(&brute),
+                generator: build_or_exit(&generator),
+                size,
+                checker: checker.as_ref(),
+                limits: judge.limits(&file_name),
+            };
+
+            let seeds = seed..iterations.map_or(u64::MAX, |iterations| seed + iterations);
+            let mismatch = stress.run(seeds).unwrap_or_else(|err| {
+                eprintln!("failed to run stress test: {}", err);
+                process::exit(1);
+            });
+            if let Some(mut mismatch) = mismatch {
+                if size.is_some() || shrink.is_some() {
+                    let before = mismatch.input.len();
+                    mismatch = stress.shrink(mismatch, shrink).unwrap_or_else(|err| {
+                        eprintln!("failed to shrink failing test: {}", err);
+                        process::exit(1);
+                    });
+                    println!(
+                        "shrunk input from {} to {} bytes",
+                        before,
+                        mismatch.input.len()
+                    );
+                }
+                let path = mismatch
+                    .save(&judge::test_dir(&file_name))
+                    .unwrap_or_else(|err| {
+                        eprintln!("failed to save failing test: {}", err);
+                        process::exit(1);
+                    });
+                print!("seed {}: {}", mismatch.seed, mismatch.verdict);
+                match mismatch.message {
+                    Some(message) => println!(": {}", message),
+                    None => println!(),
+                }
+                if mismatch.verdict == Verdict::WrongAnswer {
+                    let name = format!("seed {}", mismatch.seed);
+                    let options = judge.diff_options();
+                    print_diff(&name, &mismatch.expected, &mismatch.actual, &options);
+                }
+                println!("saved to {}", path.display());
+                process::exit(1);
+            }
         }
 
         SubCommand::Commit { file_name } => {
-            let repo = Repository::open(&Path::new(".")).expect("failed to open repository");
+            let repo = Repository::open(Path::new(".")).expect("failed to open repository");
             let mut index = repo.index().expect("failed to get index");
             let path = format!("src/bin/{}.rs", file_name);
             let path = Path::new(&path);
@@ -159,10 +594,39 @@ async fn main() -> Result<(), Error> {
             .expect("failed to commit");
         }
 
-        SubCommand::Submit { file_name } => {
+        SubCommand::Bundle {
+            file_name,
+            output,
+            bundle,
+        } => {
+            let code = bundle_or_exit(&file_name, &bundle);
+            if code.len() > bundle::SOURCE_LIMIT {
+                eprintln!(
+                    "warning: the bundle is {} bytes, over the limit of {} bytes",
+                    code.len(),
+                    bundle::SOURCE_LIMIT
+                );
+            }
+            match output {
+                Some(output) => fs::write(output, code).expect("failed to write bundle"),
+                None => print!("{}", code),
+            }
+        }
+
+        SubCommand::Submit { file_name, bundle } => {
+            let code = bundle_or_exit(&file_name, &bundle);
+            if code.len() > bundle::SOURCE_LIMIT {
+                eprintln!(
+                    "the bundle is {} bytes, over the limit of {} bytes; \
+                     try --strip-comments and --strip-tests",
+                    code.len(),
+                    bundle::SOURCE_LIMIT
+                );
+                process::exit(1);
+            }
             let mut cf = webdriver::init().await;
             cf.login().await.expect("cannot login");
-            cf.submit(file_name).await.expect("submit error");
+            cf.submit(file_name, &code).await.expect("submit error");
             cf.exit().await.expect("exit with error");
         }
 
@@ -0,0 +1,371 @@
+//! Integers modulo a fixed or runtime modulus.
+use std::{
+    fmt,
+    hash::Hash,
+    iter::{Product, Sum},
+    marker::PhantomData,
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    str::FromStr,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use crate::num::{FromUsize, One, Zero};
+
+/// Where a [`ModInt`] gets its modulus from.
+pub trait Modulus: Clone + Copy + PartialEq + Eq + Hash + Default + fmt::Debug {
+    fn modulus() -> u32;
+}
+
+/// Modulus fixed at compile time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ConstModulus<const M: u32>;
+
+impl<const M: u32> Modulus for ConstModulus<M> {
+    fn modulus() -> u32 {
+        M
+    }
+}
+
+static DYNAMIC_MODULUS: AtomicU32 = AtomicU32::new(998244353);
+
+/// Modulus set at runtime with [`DynamicModInt::set_modulus`], shared by all threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DynamicModulus;
+
+impl Modulus for DynamicModulus {
+    fn modulus() -> u32 {
+        DYNAMIC_MODULUS.load(Ordering::Relaxed)
+    }
+}
+
+/// An integer modulo `M::modulus()`, stored in `[0, modulus)`.
+///
+/// Arithmetic operators accept anything convertible into a `ModInt`, so `a * 2` and
+/// `a + b` both work. Division requires the divisor to be coprime with the modulus.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ModInt<M: Modulus> {
+    value: u32,
+    modulus: PhantomData<M>,
+}
+
+pub type StaticModInt<const M: u32> = ModInt<ConstModulus<M>>;
+pub type ModInt998244353 = StaticModInt<998244353>;
+pub type ModInt1000000007 = StaticModInt<1000000007>;
+pub type DynamicModInt = ModInt<DynamicModulus>;
+
+impl DynamicModInt {
+    /// Set the modulus of every `DynamicModInt`; values created before keep their
+    /// representatives and should not be mixed with new ones.
+    pub fn set_modulus(modulus: u32) {
+        assert!(modulus >= 1, "modulus must be positive");
+        DYNAMIC_MODULUS.store(modulus, Ordering::Relaxed);
+    }
+}
+
+impl<M: Modulus> ModInt<M> {
+    pub fn new(value: impl Into<Self>) -> Self {
+        value.into()
+    }
+
+    /// A `ModInt` from a value already in `[0, modulus)`.
+    pub fn raw(value: u32) -> Self {
+        debug_assert!(value < M::modulus());
+        ModInt {
+            value,
+            modulus: PhantomData,
+        }
+    }
+
+    pub fn modulus() -> u32 {
+        M::modulus()
+    }
+
+    /// The representative in `[0, modulus)`.
+    pub fn val(self) -> u32 {
+        self.value
+    }
+
+    pub fn pow(self, mut exp: u64) -> Self {
+        let mut base = self;
+        let mut result = Self::raw(1 % M::modulus());
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// The multiplicative inverse, or `None` if the value is not coprime with the modulus.
+    pub fn checked_inv(self) -> Option<Self> {
+        // Extended Euclid on (value, modulus), tracking only the coefficient of `value`.
+        let (mut a, mut b) = (i64::from(self.value), i64::from(M::modulus()));
+        let (mut x, mut y) = (1i64, 0i64);
+        while b != 0 {
+            let q = a / b;
+            (a, b) = (b, a - q * b);
+            (x, y) = (y, x - q * y);
+        }
+        (a == 1).then(|| Self::from(x))
+    }
+
+    /// The multiplicative inverse; panics if there is none.
+    pub fn inv(self) -> Self {
+        self.checked_inv()
+            .unwrap_or_else(|| panic!("{} has no inverse modulo {}", self, M::modulus()))
+    }
+}
+
+macro_rules! impl_from_unsigned {
+    ($($t:ty),*) => {$(
+        impl<M: Modulus> From<$t> for ModInt<M> {
+            fn from(value: $t) -> Self {
+                Self::raw((value as u128 % M::modulus() as u128) as u32)
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_from_signed {
+    ($($t:ty),*) => {$(
+        impl<M: Modulus> From<$t> for ModInt<M> {
+            fn from(value: $t) -> Self {
+                Self::raw((value as i128).rem_euclid(M::modulus() as i128) as u32)
+            }
+        }
+    )*};
+}
+
+impl_from_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_from_signed!(i8, i16, i32, i64, i128, isize);
+
+impl<M: Modulus> From<bool> for ModInt<M> {
+    fn from(value: bool) -> Self {
+        Self::from(u32::from(value))
+    }
+}
+
+impl<M: Modulus, T: Into<ModInt<M>>> Add<T> for ModInt<M> {
+    type Output = Self;
+
+    fn add(self, rhs: T) -> Self {
+        let m = M::modulus();
+        let (sum, overflow) = self.value.overflowing_add(rhs.into().value);
+        Self::raw(if overflow || sum >= m {
+            sum.wrapping_sub(m)
+        } else {
+            sum
+        })
+    }
+}
+
+impl<M: Modulus, T: Into<ModInt<M>>> Sub<T> for ModInt<M> {
+    type Output = Self;
+
+    fn sub(self, rhs: T) -> Self {
+        let rhs = rhs.into().value;
+        Self::raw(if self.value >= rhs {
+            self.value - rhs
+        } else {
+            self.value.wrapping_sub(rhs).wrapping_add(M::modulus())
+        })
+    }
+}
+
+impl<M: Modulus, T: Into<ModInt<M>>> Mul<T> for ModInt<M> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self {
+        let product = u64::from(self.value) * u64::from(rhs.into().value);
+        Self::raw((product % u64::from(M::modulus())) as u32)
+    }
+}
+
+impl<M: Modulus, T: Into<ModInt<M>>> Div<T> for ModInt<M> {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: T) -> Self {
+        self * rhs.into().inv()
+    }
+}
+
+macro_rules! impl_assign {
+    ($($trait:ident::$method:ident => $op:tt),*) => {$(
+        impl<M: Modulus, T: Into<ModInt<M>>> $trait<T> for ModInt<M> {
+            fn $method(&mut self, rhs: T) {
+                *self = *self $op rhs;
+            }
+        }
+    )*};
+}
+
+impl_assign!(
+    AddAssign::add_assign => +,
+    SubAssign::sub_assign => -,
+    MulAssign::mul_assign => *,
+    DivAssign::div_assign => /
+);
+
+impl<M: Modulus> Zero for ModInt<M> {
+    fn zero() -> Self {
+        Self::raw(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.value == 0
+    }
+}
+
+impl<M: Modulus> One for ModInt<M> {
+    fn one() -> Self {
+        Self::from(1u32)
+    }
+}
+
+impl<M: Modulus> FromUsize for ModInt<M> {
+    fn from_usize(n: usize) -> Self {
+        Self::from(n)
+    }
+}
+
+impl<M: Modulus> Neg for ModInt<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::raw(0) - self
+    }
+}
+
+impl<M: Modulus> Sum for ModInt<M> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::raw(0), |acc, x| acc + x)
+    }
+}
+
+impl<'a, M: Modulus> Sum<&'a Self> for ModInt<M> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl<M: Modulus> Product for ModInt<M> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::from(1u32), |acc, x| acc * x)
+    }
+}
+
+impl<'a, M: Modulus> Product<&'a Self> for ModInt<M> {
+    fn product<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.copied().product()
+    }
+}
+
+impl<M: Modulus> fmt::Display for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<M: Modulus> fmt::Debug for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+/// Error from parsing a [`ModInt`] that is not an optionally signed decimal integer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseModIntError(String);
+
+impl fmt::Display for ParseModIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid integer `{}`", self.0)
+    }
+}
+
+impl std::error::Error for ParseModIntError {}
+
+impl<M: Modulus> FromStr for ModInt<M> {
+    type Err = ParseModIntError;
+
+    /// Parse a decimal integer of any length, reducing it modulo the modulus.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseModIntError(s.to_string()));
+        }
+        let value = digits
+            .bytes()
+            .fold(Self::raw(0), |acc, b| acc * 10 + (b - b'0'));
+        Ok(if negative { -value } else { value })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Mint = ModInt998244353;
+
+    #[test]
+    fn test_static() {
+        let a = Mint::new(-1);
+        assert_eq!(a.val(), 998244352);
+        assert_eq!(a + 2, Mint::new(1));
+        assert_eq!(Mint::new(3) - 5, Mint::new(-2));
+        assert_eq!(a * a, Mint::new(1));
+        assert_eq!(-Mint::new(0), Mint::new(0));
+        assert_eq!(Mint::new(2).pow(23) * 119 + 1, Mint::new(0));
+        assert_eq!(Mint::new(3).inv() * 3, Mint::new(1));
+        assert_eq!(Mint::new(1) / 2 * 2, Mint::new(1));
+        assert_eq!(Mint::new(0).checked_inv(), None);
+
+        let mut b = ModInt1000000007::new(1u64 << 40);
+        b += 1;
+        b *= 2;
+        b -= 3;
+        b /= 5;
+        assert_eq!(b * 5 + 1, ModInt1000000007::new((1u64 << 41) % 1000000007));
+
+        assert_eq!(
+            (1..=10).map(Mint::new).product::<Mint>(),
+            Mint::new(3628800)
+        );
+        assert_eq!([Mint::new(-1); 3].iter().sum::<Mint>(), Mint::new(-3));
+        assert_eq!(StaticModInt::<1>::new(5).pow(0).val(), 0);
+        assert_eq!(
+            StaticModInt::<4294967291>::new(-1) + (-1),
+            StaticModInt::new(-2)
+        );
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        assert_eq!("998244354".parse::<Mint>(), Ok(Mint::new(1)));
+        assert_eq!("-1".parse::<Mint>(), Ok(Mint::new(-1)));
+        assert_eq!(
+            "100000000000000000000".parse::<ModInt1000000007>(),
+            Ok(ModInt1000000007::new(100000000000000000000u128))
+        );
+        assert!("".parse::<Mint>().is_err());
+        assert!("1e9".parse::<Mint>().is_err());
+        assert_eq!(Mint::new(-1).to_string(), "998244352");
+        assert_eq!(format!("{:?}", vec![Mint::new(1)]), "[1]");
+    }
+
+    #[test]
+    fn test_dynamic() {
+        DynamicModInt::set_modulus(6);
+        let a = DynamicModInt::new(-1);
+        assert_eq!(a.val(), 5);
+        assert_eq!(DynamicModInt::modulus(), 6);
+        assert_eq!(a.inv(), a);
+        assert_eq!(DynamicModInt::new(2).checked_inv(), None);
+        assert_eq!(DynamicModInt::new(5).pow(3), DynamicModInt::new(5));
+    }
+}
@@ -0,0 +1,77 @@
+//! Numeric traits for the generic algorithms of the library.
+
+/// The additive identity.
+pub trait Zero: Sized {
+    fn zero() -> Self;
+
+    fn is_zero(&self) -> bool;
+}
+
+macro_rules! impl_zero {
+    ($zero:literal: $($t:ty),*) => {$(
+        impl Zero for $t {
+            fn zero() -> Self {
+                $zero
+            }
+
+            fn is_zero(&self) -> bool {
+                *self == $zero
+            }
+        }
+    )*};
+}
+
+impl_zero!(0: u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+impl_zero!(0.0: f32, f64);
+
+/// The multiplicative identity.
+pub trait One: Sized {
+    fn one() -> Self;
+}
+
+/// Smallest and largest values of a type.
+pub trait Bounded {
+    fn min_value() -> Self;
+
+    fn max_value() -> Self;
+}
+
+macro_rules! impl_one_bounded {
+    ($one:literal: $($t:ty),*) => {$(
+        impl One for $t {
+            fn one() -> Self {
+                $one
+            }
+        }
+
+        impl Bounded for $t {
+            fn min_value() -> Self {
+                <$t>::MIN
+            }
+
+            fn max_value() -> Self {
+                <$t>::MAX
+            }
+        }
+    )*};
+}
+
+impl_one_bounded!(1: u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+impl_one_bounded!(1.0: f32, f64);
+
+/// Conversion from an index or a count, e.g. to multiply by a length.
+pub trait FromUsize {
+    fn from_usize(n: usize) -> Self;
+}
+
+macro_rules! impl_from_usize {
+    ($($t:ty),*) => {$(
+        impl FromUsize for $t {
+            fn from_usize(n: usize) -> Self {
+                n as $t
+            }
+        }
+    )*};
+}
+
+impl_from_usize!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
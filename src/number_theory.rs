@@ -0,0 +1,362 @@
+//! Divisibility, primes and factorization.
+
+pub fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple; `lcm(0, x)` is 0.
+pub fn lcm(a: u64, b: u64) -> u64 {
+    if a == 0 || b == 0 {
+        0
+    } else {
+        a / gcd(a, b) * b
+    }
+}
+
+/// `(g, x, y)` with `a * x + b * y = g = gcd(a, b)` and `g >= 0`.
+pub fn ext_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut r0, mut r1) = (a, b);
+    let (mut x0, mut x1) = (1, 0);
+    let (mut y0, mut y1) = (0, 1);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+    if r0 < 0 {
+        (-r0, -x0, -y0)
+    } else {
+        (r0, x0, y0)
+    }
+}
+
+/// Inverse of `a` modulo `m`, in `[0, m)`, if `a` and `m` are coprime.
+pub fn inv_mod(a: i64, m: i64) -> Option<i64> {
+    assert!(m >= 1, "modulus must be positive");
+    let (g, x, _) = ext_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// `a^exp mod m`.
+pub fn pow_mod(a: u64, mut exp: u64, m: u64) -> u64 {
+    let mul = |a: u64, b: u64| (u128::from(a) * u128::from(b) % u128::from(m)) as u64;
+    let mut base = a % m;
+    let mut result = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul(result, base);
+        }
+        base = mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Chinese remainder theorem: `(x, l)` such that `y ≡ r[i] (mod m[i])` for all `i` exactly
+/// when `y ≡ x (mod l)`, with `l` the lcm of the moduli and `0 <= x < l`.
+///
+/// Returns `None` if there is no solution or `l` does not fit in an `i64`; `(0, 1)` for no
+/// congruences.
+pub fn crt(r: &[i64], m: &[i64]) -> Option<(i64, i64)> {
+    assert_eq!(r.len(), m.len());
+    let (mut x, mut l) = (0i128, 1i128);
+    for (&ri, &mi) in r.iter().zip(m) {
+        assert!(mi >= 1, "moduli must be positive");
+        let (ri, mi) = (i128::from(ri).rem_euclid(i128::from(mi)), i128::from(mi));
+        // Solve x + l * t ≡ ri (mod mi) for t.
+        let (g, p, _) = ext_gcd(
+            i64::try_from(l % mi).expect("reduced below mi"),
+            i64::try_from(mi).expect("mi is an i64"),
+        );
+        let g = i128::from(g);
+        if (ri - x) % g != 0 {
+            return None;
+        }
+        let step = mi / g;
+        let t = ((ri - x) / g % step * i128::from(p)).rem_euclid(step);
+        x += l * t;
+        l *= step;
+        if l > i128::from(i64::MAX) {
+            return None;
+        }
+    }
+    Some((x as i64, l as i64))
+}
+
+/// Linear sieve of the smallest prime factor of every number up to `n`.
+pub struct Sieve {
+    spf: Vec<u32>,
+    primes: Vec<u32>,
+}
+
+impl Sieve {
+    pub fn new(n: usize) -> Sieve {
+        let mut spf = vec![0u32; n + 1];
+        let mut primes = Vec::new();
+        for i in 2..=n {
+            if spf[i] == 0 {
+                spf[i] = i as u32;
+                primes.push(i as u32);
+            }
+            for &p in &primes {
+                if p > spf[i] || i * p as usize > n {
+                    break;
+                }
+                spf[i * p as usize] = p;
+            }
+        }
+        Sieve { spf, primes }
+    }
+
+    /// The primes up to `n`, in increasing order.
+    pub fn primes(&self) -> &[u32] {
+        &self.primes
+    }
+
+    /// Smallest prime factor of `x`, for `2 <= x <= n`.
+    pub fn spf(&self, x: usize) -> u32 {
+        self.spf[x]
+    }
+
+    pub fn is_prime(&self, x: usize) -> bool {
+        x >= 2 && self.spf[x] as usize == x
+    }
+
+    /// Prime factors of `1 <= x <= n` with their exponents, in increasing order.
+    pub fn factorize(&self, mut x: usize) -> Vec<(u32, u32)> {
+        let mut factors: Vec<(u32, u32)> = Vec::new();
+        while x > 1 {
+            let p = self.spf[x];
+            match factors.last_mut() {
+                Some((q, e)) if *q == p => *e += 1,
+                _ => factors.push((p, 1)),
+            }
+            x /= p as usize;
+        }
+        factors
+    }
+}
+
+/// Deterministic Miller–Rabin test.
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        if n % p == 0 {
+            return n == p;
+        }
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    // These bases are enough for every `u64`.
+    [2, 325, 9375, 28178, 450775, 9780504, 1795265022]
+        .iter()
+        .all(|&a| {
+            let a = a % n;
+            if a == 0 {
+                return true;
+            }
+            let mut x = pow_mod(a, d, n);
+            if x == 1 || x == n - 1 {
+                return true;
+            }
+            for _ in 1..s {
+                x = (u128::from(x) * u128::from(x) % u128::from(n)) as u64;
+                if x == n - 1 {
+                    return true;
+                }
+            }
+            false
+        })
+}
+
+/// A nontrivial factor of an odd composite `n`, by Pollard's rho with Brent's cycle detection.
+fn pollard_rho(n: u64) -> u64 {
+    let mul = |a: u64, b: u64| (u128::from(a) * u128::from(b) % u128::from(n)) as u64;
+    for c in 1.. {
+        let f = |x: u64| ((u128::from(x) * u128::from(x) + c) % u128::from(n)) as u64;
+        let (mut x, mut y, mut ys) = (0, 2, 2);
+        let (mut g, mut q, mut r) = (1, 1, 1);
+        // Multiply differences in batches to take fewer gcds.
+        const BATCH: u64 = 128;
+        while g == 1 {
+            x = y;
+            for _ in 0..r {
+                y = f(y);
+            }
+            let mut k = 0;
+            while k < r && g == 1 {
+                ys = y;
+                for _ in 0..BATCH.min(r - k) {
+                    y = f(y);
+                    q = mul(q, x.abs_diff(y));
+                }
+                g = gcd(q, n);
+                k += BATCH;
+            }
+            r *= 2;
+        }
+        if g == n {
+            // The batch overshot; redo its steps one at a time.
+            loop {
+                ys = f(ys);
+                g = gcd(x.abs_diff(ys), n);
+                if g > 1 {
+                    break;
+                }
+            }
+        }
+        if g != n {
+            return g;
+        }
+    }
+    unreachable!()
+}
+
+/// Prime factors of `n >= 1` with their exponents, in increasing order.
+pub fn factorize(mut n: u64) -> Vec<(u64, u32)> {
+    assert!(n >= 1, "cannot factorize 0");
+    let mut primes = Vec::new();
+    for p in [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+        while n % p == 0 {
+            primes.push(p);
+            n /= p;
+        }
+    }
+    let mut stack = vec![n];
+    while let Some(n) = stack.pop() {
+        if n == 1 {
+            continue;
+        }
+        if is_prime(n) {
+            primes.push(n);
+        } else {
+            let d = pollard_rho(n);
+            stack.push(d);
+            stack.push(n / d);
+        }
+    }
+    primes.sort_unstable();
+    let mut factors: Vec<(u64, u32)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((q, e)) if *q == p => *e += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    factors
+}
+
+/// Divisors of `n >= 1`, in increasing order.
+pub fn divisors(n: u64) -> Vec<u64> {
+    let mut divisors = vec![1];
+    for (p, e) in factorize(n) {
+        let len = divisors.len();
+        let mut power = 1;
+        for _ in 0..e {
+            power *= p;
+            for i in 0..len {
+                divisors.push(divisors[i] * power);
+            }
+        }
+    }
+    divisors.sort_unstable();
+    divisors
+}
+
+/// Euler's totient: how many of `1..=n` are coprime with `n`.
+pub fn totient(n: u64) -> u64 {
+    if n == 0 {
+        return 0;
+    }
+    factorize(n)
+        .into_iter()
+        .fold(n, |phi, (p, _)| phi / p * (p - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(0, 5), 5);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(lcm(0, 6), 0);
+        for (a, b) in [(240, 46), (-3, 7), (0, -4), (5, 0)] {
+            let (g, x, y) = ext_gcd(a, b);
+            assert_eq!(g, gcd(a.unsigned_abs(), b.unsigned_abs()) as i64);
+            assert_eq!(a * x + b * y, g);
+        }
+        assert_eq!(inv_mod(3, 7), Some(5));
+        assert_eq!(inv_mod(-3, 7), Some(2));
+        assert_eq!(inv_mod(2, 4), None);
+        assert_eq!(pow_mod(3, 200, 1_000_000_007), 136_318_165);
+        assert_eq!(pow_mod(u64::MAX, 2, u64::MAX - 1), 1);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[2, 3, 2], &[3, 5, 7]), Some((23, 105)));
+        assert_eq!(crt(&[1, 3], &[4, 6]), Some((9, 12)));
+        assert_eq!(crt(&[1, 2], &[4, 6]), None);
+        assert_eq!(crt(&[-1], &[5]), Some((4, 5)));
+        assert_eq!(crt(&[], &[]), Some((0, 1)));
+        let (x, l) = crt(&[1, 2], &[1_000_000_007, 998_244_353]).unwrap();
+        assert_eq!(l, 1_000_000_007 * 998_244_353);
+        assert_eq!((x % 1_000_000_007, x % 998_244_353), (1, 2));
+    }
+
+    #[test]
+    fn test_sieve() {
+        let sieve = Sieve::new(100);
+        assert_eq!(sieve.primes().len(), 25);
+        assert_eq!(&sieve.primes()[..5], [2, 3, 5, 7, 11]);
+        assert_eq!(sieve.spf(91), 7);
+        assert!(sieve.is_prime(97));
+        assert!(!sieve.is_prime(1));
+        assert_eq!(sieve.factorize(72), [(2, 3), (3, 2)]);
+        assert_eq!(sieve.factorize(1), []);
+        for x in 2..=100 {
+            assert_eq!(sieve.is_prime(x), is_prime(x as u64));
+        }
+    }
+
+    #[test]
+    fn test_factorize() {
+        assert!(is_prime(998_244_353));
+        assert!(is_prime(18_446_744_073_709_551_557));
+        assert!(!is_prime(3_215_031_751));
+        assert!(!is_prime(1));
+        assert_eq!(factorize(1), []);
+        assert_eq!(factorize(360), [(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(
+            factorize(1_000_000_007 * 998_244_353),
+            [(998_244_353, 1), (1_000_000_007, 1)]
+        );
+        assert_eq!(
+            factorize(u64::MAX),
+            [
+                (3, 1),
+                (5, 1),
+                (17, 1),
+                (257, 1),
+                (641, 1),
+                (65537, 1),
+                (6_700_417, 1)
+            ]
+        );
+        assert_eq!(factorize(1_000_000_007u64.pow(2)), [(1_000_000_007, 2)]);
+        assert_eq!(divisors(12), [1, 2, 3, 4, 6, 12]);
+        assert_eq!(divisors(1), [1]);
+        assert_eq!(totient(1), 1);
+        assert_eq!(totient(36), 12);
+        assert_eq!(totient(97), 96);
+    }
+}
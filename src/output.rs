@@ -0,0 +1,150 @@
+//! Buffered output.
+use std::{
+    fmt::Display,
+    io::{self, BufWriter, StdoutLock, Write},
+};
+
+/// Buffered writer, flushed when dropped.
+///
+/// `println!` locks and flushes stdout on every call, which is slow for large outputs, so
+/// solutions write through a single `Output` instead:
+///
+/// ```
+/// use std::io::Write;
+///
+/// use cp_rust::{output::Output, print_lines, print_words, puts};
+///
+/// let (n, m, a, x) = (3, 2, vec![1, 2, 3], 0.5);
+/// let mut buf = Vec::new();
+/// {
+///     // `Output::stdout()` in a solution.
+///     let mut out = Output::new(&mut buf);
+///     puts!(out, n, m);
+///     print_words!(out, &a);
+///     print_lines!(out, a.iter().map(|x| x * 2));
+///     out.yes_no(n > m);
+///     writeln!(out, "{:.9}", x).unwrap();
+/// }
+/// assert_eq!(buf, b"3 2\n1 2 3\n2\n4\n6\nYES\n0.500000000\n");
+/// ```
+///
+/// Interactive solutions must call [`Output::flush`] before reading the reply.
+pub struct Output<W: Write> {
+    writer: BufWriter<W>,
+}
+
+impl Output<StdoutLock<'static>> {
+    pub fn stdout() -> Self {
+        Output::new(io::stdout().lock())
+    }
+}
+
+impl<W: Write> Output<W> {
+    pub fn new(writer: W) -> Self {
+        Output {
+            writer: BufWriter::with_capacity(1 << 16, writer),
+        }
+    }
+
+    /// Write the items on one line, separated by spaces.
+    pub fn words<T: Display>(&mut self, items: impl IntoIterator<Item = T>) {
+        self.join(items, " ");
+    }
+
+    /// Write each item on its own line.
+    pub fn lines<T: Display>(&mut self, items: impl IntoIterator<Item = T>) {
+        for item in items {
+            writeln!(self.writer, "{}", item).expect("failed to write output");
+        }
+    }
+
+    /// Write the items separated by `sep`, then a newline.
+    pub fn join<T: Display>(&mut self, items: impl IntoIterator<Item = T>, sep: &str) {
+        for (i, item) in items.into_iter().enumerate() {
+            if i > 0 {
+                self.writer
+                    .write_all(sep.as_bytes())
+                    .expect("failed to write output");
+            }
+            write!(self.writer, "{}", item).expect("failed to write output");
+        }
+        self.writer
+            .write_all(b"\n")
+            .expect("failed to write output");
+    }
+
+    /// Write `YES` or `NO` on its own line.
+    pub fn yes_no(&mut self, yes: bool) {
+        self.lines([if yes { "YES" } else { "NO" }]);
+    }
+
+    pub fn flush(&mut self) {
+        self.writer.flush().expect("failed to flush output");
+    }
+}
+
+impl<W: Write> Write for Output<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Write values of possibly different types on one line, separated by spaces.
+#[macro_export]
+macro_rules! puts {
+    ($out:expr $(,)?) => {
+        $crate::output::Output::words(&mut $out, ::std::iter::empty::<&str>())
+    };
+    ($out:expr, $first:expr $(, $rest:expr)* $(,)?) => {{
+        use ::std::io::Write as _;
+        write!($out, "{}", $first).expect("failed to write output");
+        $(write!($out, " {}", $rest).expect("failed to write output");)*
+        writeln!($out).expect("failed to write output");
+    }};
+}
+
+/// Write the items of an iterable on one line, separated by spaces.
+#[macro_export]
+macro_rules! print_words {
+    ($out:expr, $items:expr $(,)?) => {
+        $crate::output::Output::words(&mut $out, $items)
+    };
+}
+
+/// Write each item of an iterable on its own line.
+#[macro_export]
+macro_rules! print_lines {
+    ($out:expr, $items:expr $(,)?) => {
+        $crate::output::Output::lines(&mut $out, $items)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output() {
+        let mut buf = Vec::new();
+        {
+            let mut out = Output::new(&mut buf);
+            puts!(out, 1, "a", 2.5);
+            puts!(out);
+            print_words!(out, [1, 2, 3]);
+            print_words!(out, Vec::<i32>::new());
+            print_lines!(out, ["x", "y"].iter());
+            out.join(['a', 'b'], ",");
+            out.yes_no(true);
+            out.yes_no(false);
+            writeln!(out, "{:.2}", 0.5).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "1 a 2.5\n\n1 2 3\n\nx\ny\na,b\nYES\nNO\n0.50\n"
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Static prefix sums over a grid.
+use std::ops::{Add, RangeBounds, Sub};
+
+use crate::num::Zero;
+use crate::segtree::bounds;
+
+/// Rectangle sums of a fixed grid in `O(1)` after `O(hw)` preprocessing.
+#[derive(Debug, Clone)]
+pub struct PrefixSum2D<T> {
+    h: usize,
+    w: usize,
+    /// `sum[i * (w + 1) + j]` is the sum of the rectangle `0..i` by `0..j`.
+    sum: Vec<T>,
+}
+
+impl<T: Copy + Zero + Add<Output = T> + Sub<Output = T>> PrefixSum2D<T> {
+    pub fn new<R: AsRef<[T]>>(grid: &[R]) -> Self {
+        let h = grid.len();
+        let w = grid.first().map_or(0, |row| row.as_ref().len());
+        let mut sum = vec![T::zero(); (h + 1) * (w + 1)];
+        for (i, row) in grid.iter().enumerate() {
+            let row = row.as_ref();
+            assert_eq!(row.len(), w, "rows must have the same length");
+            for (j, &x) in row.iter().enumerate() {
+                sum[(i + 1) * (w + 1) + j + 1] =
+                    (sum[i * (w + 1) + j + 1] + sum[(i + 1) * (w + 1) + j] + x)
+                        - sum[i * (w + 1) + j];
+            }
+        }
+        PrefixSum2D { h, w, sum }
+    }
+
+    pub fn sum(&self, rows: impl RangeBounds<usize>, cols: impl RangeBounds<usize>) -> T {
+        let (i0, i1) = bounds(rows, self.h);
+        let (j0, j1) = bounds(cols, self.w);
+        let at = |i: usize, j: usize| self.sum[i * (self.w + 1) + j];
+        // Added before subtracting, so that unsigned types never go below zero.
+        (at(i1, j1) + at(i0, j0)) - (at(i0, j1) + at(i1, j0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_sum_2d() {
+        let grid = vec![
+            vec![1i64, -2, 3],
+            vec![4, 5, -6],
+            vec![7, 8, 9],
+            vec![0, 1, 2],
+        ];
+        let prefix = PrefixSum2D::new(&grid);
+        for i0 in 0..=4 {
+            for i1 in i0..=4 {
+                for j0 in 0..=3 {
+                    for j1 in j0..=3 {
+                        let expected: i64 = grid[i0..i1]
+                            .iter()
+                            .map(|row| row[j0..j1].iter().sum::<i64>())
+                            .sum();
+                        assert_eq!(prefix.sum(i0..i1, j0..j1), expected);
+                    }
+                }
+            }
+        }
+        assert_eq!(prefix.sum(.., 1..=1), 12);
+
+        let ones = PrefixSum2D::new(&[[1u32; 5]; 2]);
+        assert_eq!(ones.sum(.., ..), 10);
+        assert_eq!(ones.sum(1..2, 1..4), 3);
+        let corner = PrefixSum2D::new(&[[5u32, 0], [0, 0]]);
+        assert_eq!(corner.sum(1..2, 1..2), 0);
+        assert_eq!(corner.sum(..1, ..1), 5);
+        assert_eq!(PrefixSum2D::<u8>::new::<Vec<u8>>(&[]).sum(.., ..), 0);
+    }
+}
@@ -0,0 +1,506 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    ops::Range,
+};
+
+use crate::bundle::parse_use_tree;
+use crate::lexer::{self, Kind, Token};
+
+/// What an item is, as far as reachability is concerned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ItemKind {
+    /// A definition, needed once its name is used; traits also once one of their methods is.
+    Definition(String, Vec<String>),
+    /// A `use` declaration, needed once any name it brings in is used.
+    Use(Vec<String>),
+    /// An `impl` block, needed once every library item named in its header is; the items of
+    /// an inherent impl are pruned one by one.
+    Impl { header: Vec<String> },
+    /// Inner attributes, glob imports and anything unrecognized.
+    Always,
+}
+
+#[derive(Debug)]
+struct Item {
+    kind: ItemKind,
+    module: usize,
+    parent: Option<usize>,
+    /// Tokens of the item, including the whitespace and comments before it.
+    tokens: Range<usize>,
+    /// Identifiers the item uses; for inherent impls only those of the header.
+    idents: Vec<String>,
+}
+
+/// Non-trivia tokens of a source, with their indices among all tokens.
+struct Code<'a> {
+    tokens: Vec<Token<'a>>,
+    code: Vec<(usize, Token<'a>)>,
+}
+
+impl<'a> Code<'a> {
+    fn new(src: &'a str) -> Code<'a> {
+        let tokens = lexer::tokenize(src);
+        let code = tokens
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, token)| !token.is_trivia())
+            .collect();
+        Code { tokens, code }
+    }
+
+    fn code_tokens(&self) -> Vec<Token<'a>> {
+        self.code.iter().map(|&(_, token)| token).collect()
+    }
+
+    /// Tokens from right after the code token before `start` to the end of code token
+    /// `end - 1`, so that removing them takes leading comments along.
+    fn span(&self, start: usize, end: usize) -> Range<usize> {
+        let from = if start == 0 {
+            0
+        } else {
+            self.code[start - 1].0 + 1
+        };
+        from..self.code[end - 1].0 + 1
+    }
+
+    /// Concatenate all tokens except those in `removed`.
+    fn without(&self, mut removed: Vec<Range<usize>>) -> String {
+        removed.sort_by_key(|range| range.start);
+        let mut out = String::new();
+        let mut i = 0;
+        for range in removed {
+            if range.start < i {
+                continue;
+            }
+            out.extend(self.tokens[i..range.start].iter().map(|token| token.text));
+            i = range.end;
+        }
+        out.extend(self.tokens[i..].iter().map(|token| token.text));
+        out
+    }
+}
+
+/// Ranges of code tokens covered by `#[cfg(test)]` items, attributes included.
+pub(crate) fn test_items(code: &[Token]) -> Vec<Range<usize>> {
+    let mut items = Vec::new();
+    let mut k = 0;
+    while k + 6 < code.len() {
+        let texts: Vec<&str> = code[k..k + 7].iter().map(|token| token.text).collect();
+        if texts != ["#", "[", "cfg", "(", "test", ")", "]"] {
+            k += 1;
+            continue;
+        }
+        let end = item_end(code, k + 7);
+        items.push(k..end);
+        k = end;
+    }
+    items
+}
+
+/// End of the item starting at `start`: after its first `;` or block at nesting depth zero.
+fn item_end(code: &[Token], start: usize) -> usize {
+    let mut depth = 0;
+    let mut k = start;
+    while k < code.len() {
+        let token = code[k];
+        if token.is_punct('(') || token.is_punct('[') || token.is_punct('{') {
+            depth += 1;
+        } else if token.is_punct(')') || token.is_punct(']') {
+            depth -= 1;
+        } else if token.is_punct('}') {
+            depth -= 1;
+            if depth == 0 {
+                // `const X: T = { ... };` ends after the semicolon.
+                let semicolon = code.get(k + 1).is_some_and(|token| token.is_punct(';'));
+                return k + 1 + usize::from(semicolon);
+            }
+        } else if token.is_punct(';') && depth == 0 {
+            return k + 1;
+        }
+        k += 1;
+    }
+    code.len()
+}
+
+/// Index right after the bracket group opening at `open`.
+fn group_end(code: &[Token], open: usize) -> usize {
+    let mut depth = 0;
+    for (k, token) in code.iter().enumerate().skip(open) {
+        if token.is_punct('(') || token.is_punct('[') || token.is_punct('{') {
+            depth += 1;
+        } else if token.is_punct(')') || token.is_punct(']') || token.is_punct('}') {
+            depth -= 1;
+            if depth == 0 {
+                return k + 1;
+            }
+        }
+    }
+    code.len()
+}
+
+/// Classify the item made of `code`, returning the index of the `{` opening the body of
+/// an inherent impl.
+fn classify(code: &[Token]) -> (ItemKind, Option<usize>) {
+    let text = |k: usize| code.get(k).map_or("", |token| token.text);
+    let mut k = 0;
+    while text(k) == "#" {
+        if text(k + 1) == "!" {
+            return (ItemKind::Always, None);
+        }
+        k = group_end(code, k + 1);
+    }
+    loop {
+        match text(k) {
+            "pub" => {
+                k += 1;
+                if text(k) == "(" {
+                    k = group_end(code, k);
+                }
+            }
+            "unsafe" | "async" | "default" | "auto" => k += 1,
+            "extern" => {
+                k += 1;
+                if code.get(k).is_some_and(|token| token.kind == Kind::Literal) {
+                    k += 1;
+                }
+            }
+            "const" if matches!(text(k + 1), "fn" | "unsafe" | "async" | "extern") => k += 1,
+            _ => break,
+        }
+    }
+
+    let definition = |k: usize| match text(k) {
+        "" | "_" => ItemKind::Always,
+        name => ItemKind::Definition(name.to_string(), Vec::new()),
+    };
+    match text(k) {
+        "trait" => {
+            // Methods declared directly in the body, at depth one.
+            let mut methods = Vec::new();
+            let mut depth = 0;
+            for (i, token) in code.iter().enumerate().skip(k) {
+                if token.is_punct('(') || token.is_punct('[') || token.is_punct('{') {
+                    depth += 1;
+                } else if token.is_punct(')') || token.is_punct(']') || token.is_punct('}') {
+                    depth -= 1;
+                } else if token.is_ident("fn") && depth == 1 {
+                    methods.push(text(i + 1).to_string());
+                }
+            }
+            match definition(k + 1) {
+                ItemKind::Definition(name, _) => (ItemKind::Definition(name, methods), None),
+                kind => (kind, None),
+            }
+        }
+        "fn" | "struct" | "enum" | "union" | "type" | "mod" | "const" => (definition(k + 1), None),
+        "static" if text(k + 1) == "mut" => (definition(k + 2), None),
+        "static" => (definition(k + 1), None),
+        "macro_rules" => (definition(k + 2), None),
+        "use" => {
+            let (leaves, _) = parse_use_tree(&code[k + 1..]);
+            let mut names = Vec::new();
+            for mut leaf in leaves {
+                if leaf.segments.last().is_some_and(|s| s == "self") {
+                    leaf.segments.pop();
+                }
+                match (leaf.alias, leaf.segments.last()) {
+                    (_, Some(last)) if last == "*" => return (ItemKind::Always, None),
+                    (Some(alias), _) => names.push(alias),
+                    (None, Some(last)) => names.push(last.clone()),
+                    (None, None) => {}
+                }
+            }
+            (ItemKind::Use(names), None)
+        }
+        "impl" => {
+            let mut depth = 0;
+            let mut inherent = true;
+            for (i, token) in code.iter().enumerate().skip(k) {
+                if token.is_punct('(') || token.is_punct('[') {
+                    depth += 1;
+                } else if token.is_punct(')') || token.is_punct(']') {
+                    depth -= 1;
+                } else if token.is_ident("for") && depth == 0 && text(i + 1) != "<" {
+                    inherent = false;
+                } else if token.is_punct('{') && depth == 0 {
+                    let header = idents(&code[k..i]);
+                    let body = if inherent { Some(i) } else { None };
+                    return (ItemKind::Impl { header }, body);
+                }
+            }
+            (ItemKind::Always, None)
+        }
+        _ => (ItemKind::Always, None),
+    }
+}
+
+fn idents(code: &[Token]) -> Vec<String> {
+    code.iter()
+        .filter(|token| token.kind == Kind::Ident)
+        .map(|token| token.text.to_string())
+        .collect()
+}
+
+/// Split the code tokens in `range` into items, recursing into inherent impls.
+fn parse_items(
+    code: &Code,
+    range: Range<usize>,
+    module: usize,
+    parent: Option<usize>,
+    items: &mut Vec<Item>,
+) {
+    let tokens = code.code_tokens();
+    let mut k = range.start;
+    while k < range.end {
+        let end = item_end(&tokens, k).min(range.end);
+        let (kind, body) = classify(&tokens[k..end]);
+        let index = items.len();
+        let own = match body {
+            Some(body) => k..k + body + 1,
+            None => k..end,
+        };
+        items.push(Item {
+            kind,
+            module,
+            parent,
+            tokens: code.span(k, end),
+            idents: idents(&tokens[own]),
+        });
+        if let Some(body) = body {
+            parse_items(code, k + body + 1..end - 1, module, Some(index), items);
+        }
+        k = end;
+    }
+}
+
+/// Remove `#[cfg(test)]` items.
+pub fn strip_tests(src: &str) -> String {
+    let code = Code::new(src);
+    let removed = test_items(&code.code_tokens())
+        .into_iter()
+        .map(|range| code.span(range.start, range.end))
+        .collect();
+    code.without(removed)
+}
+
+/// Remove comments, together with the lines they occupied alone.
+pub fn strip_comments(src: &str) -> String {
+    let tokens = lexer::tokenize(src);
+    let mut out = String::new();
+    let mut skip_newline = false;
+    for (i, token) in tokens.iter().enumerate() {
+        if token.kind != Kind::Comment {
+            let mut text = token.text;
+            if skip_newline && token.kind == Kind::Whitespace {
+                text = text.split_once('\n').map_or(text, |(_, rest)| rest);
+            }
+            skip_newline = false;
+            out.push_str(text);
+            continue;
+        }
+        let next = tokens.get(i + 1);
+        let followed_by_space = next.map_or(true, |next| next.kind == Kind::Whitespace);
+        let followed_by_newline = next.map_or(true, |next| next.text.contains('\n'));
+        let indent = out.len() - out.trim_end_matches([' ', '\t']).len();
+        let before = &out[..out.len() - indent];
+        let own_line = before.is_empty() || before.ends_with('\n');
+        if followed_by_space && followed_by_newline {
+            out.truncate(out.len() - indent);
+            skip_newline = own_line;
+        } else if !followed_by_space && !out.ends_with(char::is_whitespace) {
+            // `a/* */b` must not become `ab`.
+            out.push(' ');
+        }
+    }
+    out
+}
+
+/// Remove library items that cannot be reached from the identifiers used by `solution`.
+///
+/// Reachability goes by name: an item is kept when an identifier equal to its name appears in
+/// the solution or in a kept item, which errs on the side of keeping too much. Trait impls are
+/// kept whole, methods of inherent impls are pruned individually.
+pub fn eliminate_dead_code(solution: &str, modules: &mut BTreeMap<String, String>) {
+    let sources: Vec<String> = modules.values().cloned().collect();
+    let codes: Vec<Code> = sources.iter().map(|src| Code::new(src)).collect();
+    let mut items = Vec::new();
+    for (module, code) in codes.iter().enumerate() {
+        parse_items(code, 0..code.code.len(), module, None, &mut items);
+    }
+
+    let defined: HashSet<&str> = items
+        .iter()
+        .filter(|item| item.parent.is_none())
+        .filter_map(|item| match item.kind {
+            ItemKind::Definition(ref name, _) => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    let mut referenced: HashSet<String> = idents(&Code::new(solution).code_tokens())
+        .into_iter()
+        .collect();
+    let mut live_names: HashSet<String> = HashSet::new();
+    let mut live = vec![false; items.len()];
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (i, item) in items.iter().enumerate() {
+            if live[i] || item.parent.is_some_and(|parent| !live[parent]) {
+                continue;
+            }
+            let needed = match item.kind {
+                ItemKind::Definition(ref name, ref methods) => {
+                    referenced.contains(name)
+                        || methods.iter().any(|method| referenced.contains(method))
+                }
+                ItemKind::Use(ref names) => names.iter().any(|name| referenced.contains(name)),
+                ItemKind::Impl { ref header } => header
+                    .iter()
+                    .filter(|ident| defined.contains(ident.as_str()))
+                    .all(|ident| live_names.contains(ident)),
+                ItemKind::Always => true,
+            };
+            if needed {
+                live[i] = true;
+                changed = true;
+                referenced.extend(item.idents.iter().cloned());
+                if let ItemKind::Definition(ref name, _) = item.kind {
+                    live_names.insert(name.clone());
+                }
+            }
+        }
+    }
+
+    for (index, (module, code)) in modules.values_mut().zip(&codes).enumerate() {
+        let removed = items
+            .iter()
+            .zip(&live)
+            .filter(|(item, &live)| item.module == index && !live)
+            .map(|(item, _)| item.tokens.clone())
+            .collect();
+        *module = code.without(removed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_tests() {
+        let src = "pub fn f() {}\n\n#[cfg(test)]\nmod tests {\n    fn t() {}\n}\n";
+        assert_eq!(strip_tests(src), "pub fn f() {}\n");
+    }
+
+    #[test]
+    fn test_strip_comments() {
+        let src = "//! Docs.\n\n/// Doc.\nfn f() -> u8 {\n    // Alone.\n    let s = \"// kept\"; // trailing\n    1/* x */+/* y */ 2\n}\n";
+        assert_eq!(
+            strip_comments(src),
+            "\nfn f() -> u8 {\n    let s = \"// kept\";\n    1 + 2\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_eliminate_dead_code() {
+        let src = r#"use std::fmt;
+use std::collections::{HashMap, BTreeMap as Map};
+
+pub trait Shape {
+    fn area(&self) -> u64;
+}
+
+pub struct Square(pub u64);
+
+impl Square {
+    pub fn new(side: u64) -> Square {
+        Square(side)
+    }
+
+    /// Never called.
+    pub fn unused(&self) -> Map<u8, u8> {
+        Map::new()
+    }
+}
+
+impl Shape for Square {
+    fn area(&self) -> u64 {
+        helper(self.0)
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+fn helper(x: u64) -> u64 {
+    x * x
+}
+
+pub struct Circle;
+
+impl Shape for Circle {
+    fn area(&self) -> u64 {
+        3
+    }
+}
+
+#[macro_export]
+macro_rules! square {
+    ($x:expr) => {
+        $crate::cp_rust::shapes::Square::new($x)
+    };
+}
+
+const _: () = ();
+"#;
+        let mut modules = BTreeMap::from([("shapes".to_string(), src.to_string())]);
+        eliminate_dead_code("fn main() { square!(2).area(); }", &mut modules);
+        assert_eq!(
+            modules["shapes"],
+            r#"use std::fmt;
+
+pub trait Shape {
+    fn area(&self) -> u64;
+}
+
+pub struct Square(pub u64);
+
+impl Square {
+    pub fn new(side: u64) -> Square {
+        Square(side)
+    }
+}
+
+impl Shape for Square {
+    fn area(&self) -> u64 {
+        helper(self.0)
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+fn helper(x: u64) -> u64 {
+    x * x
+}
+
+#[macro_export]
+macro_rules! square {
+    ($x:expr) => {
+        $crate::cp_rust::shapes::Square::new($x)
+    };
+}
+
+const _: () = ();
+"#
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use std::{
+    fmt,
+    io::{self, Read},
+    process::{Child, Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+/// Interval between two checks of a running child.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Resource limits applied to a single run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// CPU time limit.
+    pub time: Duration,
+    /// Wall clock limit, after which the child is killed.
+    pub wall_time: Duration,
+    /// Peak resident memory limit, in bytes.
+    pub memory: u64,
+}
+
+impl Limits {
+    /// Limits with the wall clock limit set to twice the CPU time limit.
+    pub fn new(time: Duration, memory: u64) -> Limits {
+        Limits {
+            time,
+            wall_time: time * 2,
+            memory,
+        }
+    }
+}
+
+impl Default for Limits {
+    /// Codeforces' most common limits: 2 seconds and 256 megabytes.
+    fn default() -> Limits {
+        Limits::new(Duration::from_secs(2), 256 << 20)
+    }
+}
+
+/// How a child process ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Termination {
+    Exited(i32),
+    Signaled(i32),
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Termination::Exited(code) => write!(f, "exit code {}", code),
+            Termination::Signaled(signal) => write!(f, "signal {}", signal),
+        }
+    }
+}
+
+/// Resources consumed by a child process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// User plus system CPU time.
+    pub time: Duration,
+    pub wall_time: Duration,
+    /// Peak resident memory, in bytes.
+    pub memory: u64,
+}
+
+/// The reason a child was killed before it finished on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Killed {
+    Time,
+    Memory,
+}
+
+/// Everything observed about one finished run.
+#[derive(Debug)]
+pub struct Execution {
+    pub termination: Termination,
+    pub usage: Usage,
+    pub killed: Option<Killed>,
+    pub stdout: Vec<u8>,
+}
+
+impl Execution {
+    pub fn exceeded_time(&self, limits: &Limits) -> bool {
+        self.killed == Some(Killed::Time)
+            || self.usage.time > limits.time
+            || self.usage.wall_time > limits.wall_time
+    }
+
+    pub fn exceeded_memory(&self, limits: &Limits) -> bool {
+        self.killed == Some(Killed::Memory) || self.usage.memory > limits.memory
+    }
+
+    pub fn success(&self) -> bool {
+        self.termination == Termination::Exited(0)
+    }
+}
+
+/// Spawn `command` with the given stdin, capture its stdout and enforce `limits`.
+pub fn run(command: &mut Command, stdin: Stdio, limits: &Limits) -> io::Result<Execution> {
+    let mut child = spawn(command.stdin(stdin).stdout(Stdio::piped()), limits)?;
+
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let reader = thread::spawn(move || -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        stdout.read_to_end(&mut buf)?;
+        Ok(buf)
+    });
+
+    let (termination, usage, killed) = wait(&mut child, limits)?;
+    let stdout = reader.join().expect("stdout reader panicked")?;
+    Ok(Execution {
+        termination,
+        usage,
+        killed,
+        stdout,
+    })
+}
+
+/// Spawn `command` with the kernel enforcing a hard CPU limit; stdio is left to the caller.
+pub fn spawn(command: &mut Command, limits: &Limits) -> io::Result<Child> {
+    sys::limit_cpu(command, limits.time);
+    command.spawn()
+}
+
+/// Wait for an already spawned child, killing it once it exceeds `limits`.
+pub fn wait(
+    child: &mut Child,
+    limits: &Limits,
+) -> io::Result<(Termination, Usage, Option<Killed>)> {
+    sys::wait(child, limits)
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::{
+        fs, io, mem,
+        os::unix::process::CommandExt,
+        process::{Child, Command},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::{Killed, Limits, Termination, Usage, POLL_INTERVAL};
+
+    /// Let the kernel stop runaway CPU usage; the exact limit is checked after the run.
+    pub fn limit_cpu(command: &mut Command, limit: Duration) {
+        let secs = limit.as_secs() + 1;
+        unsafe {
+            command.pre_exec(move || {
+                let rlimit = libc::rlimit {
+                    rlim_cur: secs as libc::rlim_t,
+                    rlim_max: (secs + 1) as libc::rlim_t,
+                };
+                if libc::setrlimit(libc::RLIMIT_CPU, &rlimit) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+
+    pub fn wait(
+        child: &mut Child,
+        limits: &Limits,
+    ) -> io::Result<(Termination, Usage, Option<Killed>)> {
+        let pid = child.id() as libc::pid_t;
+        let start = Instant::now();
+        let mut peak = 0;
+        let mut killed = None;
+
+        loop {
+            let mut status = 0;
+            let mut rusage: libc::rusage = unsafe { mem::zeroed() };
+            let options = if killed.is_some() { 0 } else { libc::WNOHANG };
+            let ret = unsafe { libc::wait4(pid, &mut status, options, &mut rusage) };
+            if ret < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(err);
+            }
+
+            if ret == pid {
+                let termination = if libc::WIFSIGNALED(status) {
+                    Termination::Signaled(libc::WTERMSIG(status))
+                } else {
+                    Termination::Exited(libc::WEXITSTATUS(status))
+                };
+                let usage = Usage {
+                    time: timeval(rusage.ru_utime) + timeval(rusage.ru_stime),
+                    wall_time: start.elapsed(),
+                    memory: peak.max(max_rss(&rusage)),
+                };
+                return Ok((termination, usage, killed));
+            }
+
+            if start.elapsed() > limits.wall_time {
+                killed = Some(Killed::Time);
+                child.kill()?;
+                continue;
+            }
+            if let Some(rss) = current_rss(pid) {
+                peak = peak.max(rss);
+                if rss > limits.memory {
+                    killed = Some(Killed::Memory);
+                    child.kill()?;
+                    continue;
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn timeval(tv: libc::timeval) -> Duration {
+        Duration::from_secs(tv.tv_sec as u64) + Duration::from_micros(tv.tv_usec as u64)
+    }
+
+    fn max_rss(rusage: &libc::rusage) -> u64 {
+        let max_rss = rusage.ru_maxrss as u64;
+        // Linux reports kilobytes, macOS reports bytes.
+        if cfg!(target_os = "macos") {
+            max_rss
+        } else {
+            max_rss * 1024
+        }
+    }
+
+    /// Current resident set size, where `/proc` is available.
+    fn current_rss(pid: libc::pid_t) -> Option<u64> {
+        let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+        let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+        let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kb * 1024)
+    }
+}
+
+#[cfg(not(unix))]
+mod sys {
+    use std::{
+        io,
+        process::{Child, Command},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use sysinfo::{Pid, ProcessesToUpdate, System};
+
+    use super::{Killed, Limits, Termination, Usage, POLL_INTERVAL};
+
+    pub fn limit_cpu(_command: &mut Command, _limit: Duration) {}
+
+    /// Without `wait4` we can only sample memory and approximate CPU time by wall time.
+    pub fn wait(
+        child: &mut Child,
+        limits: &Limits,
+    ) -> io::Result<(Termination, Usage, Option<Killed>)> {
+        let pid = Pid::from_u32(child.id());
+        let mut system = System::new();
+        let start = Instant::now();
+        let mut peak = 0;
+        let mut killed = None;
+
+        loop {
+            if let Some(status) = child.try_wait()? {
+                let wall_time = start.elapsed();
+                let usage = Usage {
+                    time: wall_time,
+                    wall_time,
+                    memory: peak,
+                };
+                return Ok((
+                    Termination::Exited(status.code().unwrap_or(-1)),
+                    usage,
+                    killed,
+                ));
+            }
+            if killed.is_some() {
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+
+            if start.elapsed() > limits.wall_time {
+                killed = Some(Killed::Time);
+                child.kill()?;
+                continue;
+            }
+            system.refresh_processes(ProcessesToUpdate::Some(&[pid]));
+            if let Some(process) = system.process(pid) {
+                peak = peak.max(process.memory());
+                if process.memory() > limits.memory {
+                    killed = Some(Killed::Memory);
+                    child.kill()?;
+                    continue;
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn sh(script: &str, limits: &Limits) -> Execution {
+        run(
+            Command::new("sh").args(["-c", script]),
+            Stdio::null(),
+            limits,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_run() {
+        let limits = Limits::default();
+        let execution = sh("echo hello", &limits);
+        assert!(execution.success());
+        assert_eq!(execution.stdout, b"hello\n");
+        assert_eq!(execution.killed, None);
+
+        let execution = sh("exit 3", &limits);
+        assert_eq!(execution.termination, Termination::Exited(3));
+
+        let execution = sh("kill -9 $$", &limits);
+        assert_eq!(execution.termination, Termination::Signaled(9));
+    }
+
+    #[test]
+    fn test_wall_time_limit() {
+        let limits = Limits {
+            time: Duration::from_millis(100),
+            wall_time: Duration::from_millis(200),
+            memory: 256 << 20,
+        };
+        let execution = sh("exec sleep 5", &limits);
+        assert_eq!(execution.killed, Some(Killed::Time));
+        assert!(execution.exceeded_time(&limits));
+        assert!(execution.usage.wall_time < Duration::from_secs(5));
+    }
+}
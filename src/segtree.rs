@@ -0,0 +1,285 @@
+//! Segment tree over a monoid, following the AtCoder Library API.
+use std::{
+    marker::PhantomData,
+    ops::{Add, Bound, Mul, RangeBounds},
+};
+
+use crate::num::{Bounded, One, Zero};
+
+/// An associative operation with an identity element.
+pub trait Monoid {
+    type S: Clone;
+
+    fn identity() -> Self::S;
+
+    fn binary_operation(a: &Self::S, b: &Self::S) -> Self::S;
+}
+
+pub struct Max<S>(PhantomData<S>);
+
+impl<S: Copy + Ord + Bounded> Monoid for Max<S> {
+    type S = S;
+
+    fn identity() -> S {
+        S::min_value()
+    }
+
+    fn binary_operation(a: &S, b: &S) -> S {
+        *a.max(b)
+    }
+}
+
+pub struct Min<S>(PhantomData<S>);
+
+impl<S: Copy + Ord + Bounded> Monoid for Min<S> {
+    type S = S;
+
+    fn identity() -> S {
+        S::max_value()
+    }
+
+    fn binary_operation(a: &S, b: &S) -> S {
+        *a.min(b)
+    }
+}
+
+pub struct Additive<S>(PhantomData<S>);
+
+impl<S: Copy + Add<Output = S> + Zero> Monoid for Additive<S> {
+    type S = S;
+
+    fn identity() -> S {
+        S::zero()
+    }
+
+    fn binary_operation(a: &S, b: &S) -> S {
+        *a + *b
+    }
+}
+
+pub struct Multiplicative<S>(PhantomData<S>);
+
+impl<S: Copy + Mul<Output = S> + One> Monoid for Multiplicative<S> {
+    type S = S;
+
+    fn identity() -> S {
+        S::one()
+    }
+
+    fn binary_operation(a: &S, b: &S) -> S {
+        *a * *b
+    }
+}
+
+/// Half-open bounds of `range` within `0..n`.
+pub(crate) fn bounds(range: impl RangeBounds<usize>, n: usize) -> (usize, usize) {
+    let l = match range.start_bound() {
+        Bound::Included(&l) => l,
+        Bound::Excluded(&l) => l + 1,
+        Bound::Unbounded => 0,
+    };
+    let r = match range.end_bound() {
+        Bound::Included(&r) => r + 1,
+        Bound::Excluded(&r) => r,
+        Bound::Unbounded => n,
+    };
+    assert!(l <= r && r <= n, "range {}..{} out of 0..{}", l, r, n);
+    (l, r)
+}
+
+/// Point updates and range folds in `O(log n)`.
+pub struct Segtree<M: Monoid> {
+    n: usize,
+    size: usize,
+    log: usize,
+    d: Vec<M::S>,
+}
+
+impl<M: Monoid> Segtree<M> {
+    pub fn new(n: usize) -> Self {
+        vec![M::identity(); n].into()
+    }
+
+    pub fn set(&mut self, p: usize, x: M::S) {
+        assert!(p < self.n);
+        let p = p + self.size;
+        self.d[p] = x;
+        for i in 1..=self.log {
+            self.update(p >> i);
+        }
+    }
+
+    pub fn get(&self, p: usize) -> M::S {
+        assert!(p < self.n);
+        self.d[p + self.size].clone()
+    }
+
+    /// Fold of the elements in `range`.
+    pub fn prod(&self, range: impl RangeBounds<usize>) -> M::S {
+        let (mut l, mut r) = bounds(range, self.n);
+        let mut sml = M::identity();
+        let mut smr = M::identity();
+        l += self.size;
+        r += self.size;
+        while l < r {
+            if l & 1 == 1 {
+                sml = M::binary_operation(&sml, &self.d[l]);
+                l += 1;
+            }
+            if r & 1 == 1 {
+                r -= 1;
+                smr = M::binary_operation(&self.d[r], &smr);
+            }
+            l >>= 1;
+            r >>= 1;
+        }
+        M::binary_operation(&sml, &smr)
+    }
+
+    pub fn all_prod(&self) -> M::S {
+        self.d[1].clone()
+    }
+
+    /// The largest `r` such that `f(prod(l..r))` holds, assuming `f` is monotone and
+    /// `f(identity)` holds.
+    pub fn max_right<F: Fn(&M::S) -> bool>(&self, mut l: usize, f: F) -> usize {
+        assert!(l <= self.n);
+        assert!(f(&M::identity()));
+        if l == self.n {
+            return self.n;
+        }
+        l += self.size;
+        let mut sm = M::identity();
+        loop {
+            while l % 2 == 0 {
+                l >>= 1;
+            }
+            if !f(&M::binary_operation(&sm, &self.d[l])) {
+                while l < self.size {
+                    l *= 2;
+                    let res = M::binary_operation(&sm, &self.d[l]);
+                    if f(&res) {
+                        sm = res;
+                        l += 1;
+                    }
+                }
+                return l - self.size;
+            }
+            sm = M::binary_operation(&sm, &self.d[l]);
+            l += 1;
+            // Stop once `l` is a power of two, i.e. the right end was reached.
+            if l & l.wrapping_neg() == l {
+                return self.n;
+            }
+        }
+    }
+
+    /// The smallest `l` such that `f(prod(l..r))` holds, assuming `f` is monotone and
+    /// `f(identity)` holds.
+    pub fn min_left<F: Fn(&M::S) -> bool>(&self, mut r: usize, f: F) -> usize {
+        assert!(r <= self.n);
+        assert!(f(&M::identity()));
+        if r == 0 {
+            return 0;
+        }
+        r += self.size;
+        let mut sm = M::identity();
+        loop {
+            r -= 1;
+            while r > 1 && r % 2 == 1 {
+                r >>= 1;
+            }
+            if !f(&M::binary_operation(&self.d[r], &sm)) {
+                while r < self.size {
+                    r = 2 * r + 1;
+                    let res = M::binary_operation(&self.d[r], &sm);
+                    if f(&res) {
+                        sm = res;
+                        r -= 1;
+                    }
+                }
+                return r + 1 - self.size;
+            }
+            sm = M::binary_operation(&self.d[r], &sm);
+            if r & r.wrapping_neg() == r {
+                return 0;
+            }
+        }
+    }
+
+    fn update(&mut self, k: usize) {
+        self.d[k] = M::binary_operation(&self.d[2 * k], &self.d[2 * k + 1]);
+    }
+}
+
+impl<M: Monoid> From<Vec<M::S>> for Segtree<M> {
+    fn from(v: Vec<M::S>) -> Self {
+        let n = v.len();
+        let size = n.next_power_of_two();
+        let log = size.trailing_zeros() as usize;
+        let mut d = vec![M::identity(); 2 * size];
+        d[size..size + n].clone_from_slice(&v);
+        let mut tree = Segtree { n, size, log, d };
+        for i in (1..size).rev() {
+            tree.update(i);
+        }
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modint::ModInt998244353;
+
+    #[test]
+    fn test_segtree() {
+        let a = vec![3, 1, 4, 1, 5, 9, 2, 6];
+        let mut tree = Segtree::<Additive<i64>>::from(a.clone());
+        for l in 0..=a.len() {
+            for r in l..=a.len() {
+                assert_eq!(tree.prod(l..r), a[l..r].iter().sum::<i64>());
+            }
+        }
+        assert_eq!(tree.prod(..), 31);
+        assert_eq!(tree.prod(2..=3), 5);
+        tree.set(0, 10);
+        assert_eq!(tree.get(0), 10);
+        assert_eq!(tree.all_prod(), 38);
+
+        let tree = Segtree::<Max<i32>>::from(vec![1, 5, 2, 7, 3]);
+        assert_eq!(tree.prod(0..3), 5);
+        assert_eq!(tree.prod(3..3), i32::MIN);
+        assert_eq!(Segtree::<Min<u8>>::new(3).all_prod(), u8::MAX);
+        let tree = Segtree::<Additive<ModInt998244353>>::from(vec![ModInt998244353::new(-1); 2]);
+        assert_eq!(tree.all_prod(), ModInt998244353::new(-2));
+        assert_eq!(
+            Segtree::<Multiplicative<u64>>::from(vec![2, 3, 4]).all_prod(),
+            24
+        );
+    }
+
+    #[test]
+    fn test_binary_search() {
+        let a = [3, 1, 4, 1, 5, 9, 2];
+        let tree = Segtree::<Additive<i64>>::from(a.to_vec());
+        for l in 0..=a.len() {
+            for limit in 0..=32 {
+                // The longest prefix of a[l..] with sum at most `limit`.
+                let expected = (l..=a.len())
+                    .rev()
+                    .find(|&r| a[l..r].iter().sum::<i64>() <= limit)
+                    .unwrap();
+                assert_eq!(tree.max_right(l, |&s| s <= limit), expected);
+            }
+        }
+        for r in 0..=a.len() {
+            for limit in 0..=32 {
+                let expected = (0..=r)
+                    .find(|&l| a[l..r].iter().sum::<i64>() <= limit)
+                    .unwrap();
+                assert_eq!(tree.min_left(r, |&s| s <= limit), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,410 @@
+//! Single-source and all-pairs shortest paths on a [`Graph`] with per-edge weights.
+//!
+//! Weights are a slice indexed by edge id; an undirected edge has the same weight both ways.
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, VecDeque},
+    fmt,
+    ops::Add,
+};
+
+use crate::graph::Graph;
+use crate::num::Zero;
+
+/// What a weight needs to be summed and compared; integers and floats qualify.
+pub trait Weight: Copy + PartialOrd + Add<Output = Self> + Zero {}
+
+impl<T: Copy + PartialOrd + Add<Output = T> + Zero> Weight for T {}
+
+/// Distances from the sources and the last step of a shortest path to every vertex.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShortestPaths<W> {
+    /// `None` for unreachable vertices.
+    pub dist: Vec<Option<W>>,
+    /// `(previous vertex, edge id)`, `None` for sources and unreachable vertices.
+    pub pred: Vec<Option<(usize, usize)>>,
+}
+
+impl<W> ShortestPaths<W> {
+    fn new(n: usize) -> Self {
+        ShortestPaths {
+            dist: (0..n).map(|_| None).collect(),
+            pred: vec![None; n],
+        }
+    }
+
+    /// Vertices of a shortest path from a source to `target`.
+    pub fn path(&self, target: usize) -> Option<Vec<usize>> {
+        self.dist[target].as_ref()?;
+        let mut path = vec![target];
+        let mut v = target;
+        while let Some((u, _)) = self.pred[v] {
+            path.push(u);
+            v = u;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Edge ids of a shortest path from a source to `target`.
+    pub fn path_edges(&self, target: usize) -> Option<Vec<usize>> {
+        self.dist[target].as_ref()?;
+        let mut edges = Vec::new();
+        let mut v = target;
+        while let Some((u, id)) = self.pred[v] {
+            edges.push(id);
+            v = u;
+        }
+        edges.reverse();
+        Some(edges)
+    }
+
+    /// Relax the edge `id` from `u` to `v`, returning whether `v` got closer.
+    fn relax(&mut self, u: usize, v: usize, id: usize, w: W) -> bool
+    where
+        W: Weight,
+    {
+        let Some(d) = self.dist[u] else {
+            return false;
+        };
+        let d = d + w;
+        if self.dist[v].map_or(true, |old| d < old) {
+            self.dist[v] = Some(d);
+            self.pred[v] = Some((u, id));
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// A cycle of negative total weight reachable from the source, as its vertices in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NegativeCycle(pub Vec<usize>);
+
+impl fmt::Display for NegativeCycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "negative cycle through {:?}", self.0)
+    }
+}
+
+impl std::error::Error for NegativeCycle {}
+
+/// Heap entry ordered so that `BinaryHeap` pops the smallest distance first.
+struct State<W>(W, usize);
+
+impl<W: PartialOrd> PartialEq for State<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<W: PartialOrd> Eq for State<W> {}
+
+impl<W: PartialOrd> PartialOrd for State<W> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<W: PartialOrd> Ord for State<W> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .0
+            .partial_cmp(&self.0)
+            .expect("weights must be comparable")
+    }
+}
+
+/// Dijkstra's algorithm for non-negative weights, in `O((n + m) log m)`.
+pub fn dijkstra<W: Weight>(g: &Graph, weights: &[W], sources: &[usize]) -> ShortestPaths<W> {
+    assert_eq!(weights.len(), g.num_edges());
+    let mut paths = ShortestPaths::new(g.num_vertices());
+    let mut heap = BinaryHeap::new();
+    for &s in sources {
+        paths.dist[s] = Some(W::zero());
+        heap.push(State(W::zero(), s));
+    }
+    while let Some(State(d, v)) = heap.pop() {
+        if paths.dist[v].is_some_and(|best| best < d) {
+            continue;
+        }
+        for &(w, id) in g.adjacent(v) {
+            debug_assert!(weights[id] >= W::zero(), "negative weight on edge {}", id);
+            if paths.relax(v, w, id, weights[id]) {
+                heap.push(State(d + weights[id], w));
+            }
+        }
+    }
+    paths
+}
+
+/// Shortest paths when every weight is either zero or the same positive value, in
+/// `O(n + m)`.
+pub fn zero_one_bfs<W: Weight>(g: &Graph, weights: &[W], sources: &[usize]) -> ShortestPaths<W> {
+    assert_eq!(weights.len(), g.num_edges());
+    let mut paths = ShortestPaths::new(g.num_vertices());
+    let mut done = vec![false; g.num_vertices()];
+    let mut deque = VecDeque::new();
+    for &s in sources {
+        paths.dist[s] = Some(W::zero());
+        deque.push_back(s);
+    }
+    while let Some(v) = deque.pop_front() {
+        if done[v] {
+            continue;
+        }
+        done[v] = true;
+        for &(w, id) in g.adjacent(v) {
+            if paths.relax(v, w, id, weights[id]) {
+                if weights[id].is_zero() {
+                    deque.push_front(w);
+                } else {
+                    deque.push_back(w);
+                }
+            }
+        }
+    }
+    paths
+}
+
+/// The cycle the predecessors of `v` lead into, if they do.
+fn cycle_from<W>(paths: &ShortestPaths<W>, mut v: usize) -> Option<NegativeCycle> {
+    // Walking back `n` steps is sure to end up on the cycle.
+    for _ in 0..paths.pred.len() {
+        v = paths.pred[v]?.0;
+    }
+    let mut cycle = vec![v];
+    let mut u = paths.pred[v]?.0;
+    while u != v {
+        cycle.push(u);
+        u = paths.pred[u]?.0;
+    }
+    cycle.reverse();
+    Some(NegativeCycle(cycle))
+}
+
+/// Bellman–Ford for arbitrary weights, in `O(nm)`.
+///
+/// Fails with a negative cycle reachable from the source, if there is one.
+pub fn bellman_ford<W: Weight>(
+    g: &Graph,
+    weights: &[W],
+    source: usize,
+) -> Result<ShortestPaths<W>, NegativeCycle> {
+    assert_eq!(weights.len(), g.num_edges());
+    let n = g.num_vertices();
+    let mut paths = ShortestPaths::new(n);
+    paths.dist[source] = Some(W::zero());
+    for round in 0..n {
+        let mut relaxed = None;
+        for v in 0..n {
+            for &(w, id) in g.adjacent(v) {
+                if paths.relax(v, w, id, weights[id]) {
+                    relaxed = Some(w);
+                }
+            }
+        }
+        match relaxed {
+            None => return Ok(paths),
+            // Distances still improve after `n - 1` rounds only around a negative cycle.
+            Some(v) if round == n - 1 => {
+                return Err(cycle_from(&paths, v).expect("v is reached from a negative cycle"))
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(paths)
+}
+
+/// Shortest path faster algorithm: Bellman–Ford with a queue of the vertices that got
+/// closer, usually much faster in practice.
+///
+/// Fails with a negative cycle reachable from the source, if there is one.
+pub fn spfa<W: Weight>(
+    g: &Graph,
+    weights: &[W],
+    source: usize,
+) -> Result<ShortestPaths<W>, NegativeCycle> {
+    assert_eq!(weights.len(), g.num_edges());
+    let n = g.num_vertices();
+    let mut paths = ShortestPaths::new(n);
+    // Number of edges on the current path to each vertex.
+    let mut len = vec![0; n];
+    let mut queued = vec![false; n];
+    let mut queue = VecDeque::from([source]);
+    paths.dist[source] = Some(W::zero());
+    queued[source] = true;
+    while let Some(v) = queue.pop_front() {
+        queued[v] = false;
+        for &(w, id) in g.adjacent(v) {
+            if paths.relax(v, w, id, weights[id]) {
+                len[w] = len[v] + 1;
+                // A simple path has fewer than `n` edges, but `len` may be outdated, so make
+                // sure the predecessors really form a cycle.
+                if len[w] >= n {
+                    if let Some(cycle) = cycle_from(&paths, w) {
+                        return Err(cycle);
+                    }
+                }
+                if !queued[w] {
+                    queued[w] = true;
+                    queue.push_back(w);
+                }
+            }
+        }
+    }
+    Ok(paths)
+}
+
+/// Distances between all pairs of vertices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllPairs<W> {
+    /// `dist[u][v]`, `None` if `v` is unreachable from `u`.
+    pub dist: Vec<Vec<Option<W>>>,
+    /// `next[u][v]`: the vertex after `u` on a shortest path to `v`.
+    next: Vec<Vec<Option<usize>>>,
+}
+
+impl<W: Weight> AllPairs<W> {
+    /// Whether some vertex lies on a negative cycle; other distances are then meaningless.
+    pub fn has_negative_cycle(&self) -> bool {
+        self.dist
+            .iter()
+            .enumerate()
+            .any(|(v, row)| row[v].is_some_and(|d| d < W::zero()))
+    }
+
+    /// Vertices of a shortest path from `u` to `v`.
+    ///
+    /// `None` if `v` is unreachable or the graph has a negative cycle, in which case shortest
+    /// paths need not exist.
+    pub fn path(&self, mut u: usize, v: usize) -> Option<Vec<usize>> {
+        self.dist[u][v]?;
+        if self.has_negative_cycle() {
+            return None;
+        }
+        let mut path = vec![u];
+        while u != v {
+            // A simple path has at most `n` vertices.
+            if path.len() > self.dist.len() {
+                return None;
+            }
+            u = self.next[u][v]?;
+            path.push(u);
+        }
+        Some(path)
+    }
+}
+
+/// Floyd–Warshall, in `O(n^3)`.
+pub fn floyd_warshall<W: Weight>(g: &Graph, weights: &[W]) -> AllPairs<W> {
+    assert_eq!(weights.len(), g.num_edges());
+    let n = g.num_vertices();
+    let mut dist = vec![vec![None; n]; n];
+    let mut next = vec![vec![None; n]; n];
+    for v in 0..n {
+        dist[v][v] = Some(W::zero());
+        next[v][v] = Some(v);
+        for &(w, id) in g.adjacent(v) {
+            if dist[v][w].map_or(true, |d| weights[id] < d) {
+                dist[v][w] = Some(weights[id]);
+                next[v][w] = Some(w);
+            }
+        }
+    }
+    for k in 0..n {
+        for i in 0..n {
+            let Some(ik) = dist[i][k] else {
+                continue;
+            };
+            for j in 0..n {
+                let Some(kj) = dist[k][j] else {
+                    continue;
+                };
+                if dist[i][j].map_or(true, |d| ik + kj < d) {
+                    dist[i][j] = Some(ik + kj);
+                    next[i][j] = next[i][k];
+                }
+            }
+        }
+    }
+    AllPairs { dist, next }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn weighted(n: usize, edges: &[(usize, usize, i64)]) -> (Graph, Vec<i64>) {
+        let pairs: Vec<_> = edges.iter().map(|&(u, v, _)| (u, v)).collect();
+        let weights = edges.iter().map(|&(_, _, w)| w).collect();
+        (Graph::directed(n, &pairs), weights)
+    }
+
+    #[test]
+    fn test_single_source() {
+        let (g, weights) = weighted(5, &[(0, 1, 4), (0, 2, 1), (2, 1, 2), (1, 3, 1), (2, 3, 5)]);
+        let expected = [Some(0), Some(3), Some(1), Some(4), None];
+        let paths = dijkstra(&g, &weights, &[0]);
+        assert_eq!(paths.dist, expected);
+        assert_eq!(paths.path(3), Some(vec![0, 2, 1, 3]));
+        assert_eq!(paths.path_edges(3), Some(vec![1, 2, 3]));
+        assert_eq!(paths.path(4), None);
+        assert_eq!(bellman_ford(&g, &weights, 0).unwrap().dist, expected);
+        assert_eq!(spfa(&g, &weights, 0).unwrap().dist, expected);
+        assert_eq!(paths.path(0), Some(vec![0]));
+
+        let g = Graph::undirected(3, &[(0, 1), (1, 2), (0, 2)]);
+        let paths = dijkstra(&g, &[0.5, 0.25, 1.0], &[2]);
+        assert_eq!(paths.dist, [Some(0.75), Some(0.25), Some(0.0)]);
+    }
+
+    #[test]
+    fn test_zero_one_bfs() {
+        let (g, weights) = weighted(4, &[(0, 1, 1), (0, 2, 0), (2, 1, 0), (1, 3, 1)]);
+        let paths = zero_one_bfs(&g, &weights, &[0]);
+        assert_eq!(paths.dist, [Some(0), Some(0), Some(0), Some(1)]);
+        assert_eq!(paths.path(3), Some(vec![0, 2, 1, 3]));
+    }
+
+    #[test]
+    fn test_negative_weights() {
+        let (g, weights) = weighted(4, &[(0, 1, 2), (1, 2, -3), (0, 2, 1), (2, 3, 1)]);
+        let paths = bellman_ford(&g, &weights, 0).unwrap();
+        assert_eq!(paths.dist, [Some(0), Some(2), Some(-1), Some(0)]);
+        assert_eq!(spfa(&g, &weights, 0).unwrap(), paths);
+
+        let (g, weights) = weighted(
+            5,
+            &[(0, 1, 1), (1, 2, 1), (2, 3, -1), (3, 1, -1), (3, 4, 1)],
+        );
+        let mut cycle = bellman_ford(&g, &weights, 0).unwrap_err().0;
+        cycle.sort_unstable();
+        assert_eq!(cycle, [1, 2, 3]);
+        let mut cycle = spfa(&g, &weights, 0).unwrap_err().0;
+        cycle.sort_unstable();
+        assert_eq!(cycle, [1, 2, 3]);
+        // Unreachable from the source, so not reported.
+        assert!(bellman_ford(&g, &weights, 4).is_ok());
+    }
+
+    #[test]
+    fn test_floyd_warshall() {
+        let (g, weights) = weighted(4, &[(0, 1, 2), (1, 2, -3), (0, 2, 1), (2, 3, 1), (3, 3, 5)]);
+        let all = floyd_warshall(&g, &weights);
+        assert_eq!(all.dist[0], [Some(0), Some(2), Some(-1), Some(0)]);
+        assert_eq!(all.dist[3][0], None);
+        assert_eq!(all.path(0, 3), Some(vec![0, 1, 2, 3]));
+        assert_eq!(all.path(3, 0), None);
+        assert!(!all.has_negative_cycle());
+        let (g, weights) = weighted(2, &[(0, 1, 1), (1, 0, -2)]);
+        assert!(floyd_warshall(&g, &weights).has_negative_cycle());
+
+        let (g, weights) = weighted(4, &[(0, 1, 1), (1, 2, -2), (2, 1, 1), (2, 3, 1)]);
+        let all = floyd_warshall(&g, &weights);
+        assert!(all.has_negative_cycle());
+        assert_eq!(all.path(0, 3), None);
+        assert_eq!(all.path(1, 2), None);
+    }
+}
@@ -0,0 +1,142 @@
+use std::{fmt, io, str::FromStr};
+
+/// How a failing input is cut into pieces that can be removed independently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    Lines,
+    Tokens,
+}
+
+impl FromStr for Granularity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Granularity, String> {
+        match s {
+            "lines" => Ok(Granularity::Lines),
+            "tokens" => Ok(Granularity::Tokens),
+            _ => Err(format!(
+                "unknown granularity `{}`, expected lines or tokens",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Granularity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Granularity::Lines => "lines",
+            Granularity::Tokens => "tokens",
+        })
+    }
+}
+
+/// One removable piece of an input, remembering the line it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Piece<'a> {
+    line: usize,
+    text: &'a str,
+}
+
+impl Granularity {
+    fn split(self, input: &str) -> Vec<Piece<'_>> {
+        let lines = input.lines().enumerate();
+        match self {
+            Granularity::Lines => lines.map(|(line, text)| Piece { line, text }).collect(),
+            Granularity::Tokens => lines
+                .flat_map(|(line, text)| {
+                    text.split_whitespace()
+                        .map(move |text| Piece { line, text })
+                })
+                .collect(),
+        }
+    }
+
+    /// Rebuild an input from pieces, keeping tokens of the same line together.
+    fn join(self, pieces: &[Piece]) -> String {
+        let mut input = String::new();
+        for (i, piece) in pieces.iter().enumerate() {
+            if i > 0 {
+                let same_line = self == Granularity::Tokens && pieces[i - 1].line == piece.line;
+                input.push(if same_line { ' ' } else { '\n' });
+            }
+            input.push_str(piece.text);
+        }
+        if !pieces.is_empty() {
+            input.push('\n');
+        }
+        input
+    }
+}
+
+/// Minimize `input` with delta debugging, keeping only candidates for which `fails` holds.
+///
+/// `input` itself is assumed to fail. The result is 1-minimal: removing any single
+/// line or token from it makes the failure go away.
+pub fn ddmin(
+    input: &str,
+    granularity: Granularity,
+    mut fails: impl FnMut(&str) -> io::Result<bool>,
+) -> io::Result<String> {
+    let mut pieces = granularity.split(input);
+    let mut chunks = 2;
+    while pieces.len() >= 2 {
+        let chunk_len = pieces.len().div_ceil(chunks);
+        let mut reduced = false;
+        for start in (0..pieces.len()).step_by(chunk_len) {
+            let end = (start + chunk_len).min(pieces.len());
+            let complement: Vec<Piece> = pieces[..start]
+                .iter()
+                .chain(&pieces[end..])
+                .copied()
+                .collect();
+            if fails(&granularity.join(&complement))? {
+                pieces = complement;
+                chunks = (chunks - 1).max(2);
+                reduced = true;
+                break;
+            }
+        }
+        if !reduced {
+            if chunks >= pieces.len() {
+                break;
+            }
+            chunks = (chunks * 2).min(pieces.len());
+        }
+    }
+    Ok(granularity.join(&pieces))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ddmin_lines() {
+        let input = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let mut calls = 0;
+        let minimized = ddmin(input, Granularity::Lines, |candidate| {
+            calls += 1;
+            Ok(candidate.contains('3') && candidate.contains('6'))
+        })
+        .unwrap();
+        assert_eq!(minimized, "3\n6\n");
+        assert!(calls < 40);
+    }
+
+    #[test]
+    fn test_ddmin_tokens() {
+        let input = "3\n10 20 30\n";
+        let minimized = ddmin(input, Granularity::Tokens, |candidate| {
+            Ok(candidate.split_whitespace().any(|token| token == "20"))
+        })
+        .unwrap();
+        assert_eq!(minimized, "20\n");
+
+        let minimized = ddmin(input, Granularity::Tokens, |candidate| {
+            Ok(candidate.starts_with('3') && candidate.contains("10 30"))
+        })
+        .unwrap();
+        assert_eq!(minimized, "3\n10 30\n");
+    }
+}
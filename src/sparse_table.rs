@@ -0,0 +1,75 @@
+//! Sparse table for `O(1)` range queries under an idempotent operation such as `min` or `gcd`.
+use std::ops::RangeBounds;
+
+use crate::segtree::bounds;
+
+/// Static range folds of an associative and idempotent operation, built in `O(n log n)`.
+pub struct SparseTable<T, F> {
+    /// `table[k][i]` is the fold of `i..i + 2^k`.
+    table: Vec<Vec<T>>,
+    op: F,
+}
+
+impl<T: Copy, F: Fn(T, T) -> T> SparseTable<T, F> {
+    pub fn new(v: Vec<T>, op: F) -> Self {
+        let mut table = vec![v];
+        let mut k = 1;
+        while 2 * k <= table[0].len() {
+            let prev = table.last().unwrap();
+            let next = (0..prev.len() - k)
+                .map(|i| op(prev[i], prev[i + k]))
+                .collect();
+            table.push(next);
+            k *= 2;
+        }
+        SparseTable { table, op }
+    }
+
+    pub fn len(&self) -> usize {
+        self.table[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table[0].is_empty()
+    }
+
+    /// Fold of the elements in `range`, which must not be empty.
+    pub fn query(&self, range: impl RangeBounds<usize>) -> T {
+        let (l, r) = bounds(range, self.len());
+        assert!(l < r, "empty range {}..{}", l, r);
+        let k = (r - l).ilog2() as usize;
+        (self.op)(self.table[k][l], self.table[k][r - (1 << k)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::number_theory::gcd;
+
+    #[test]
+    fn test_sparse_table() {
+        let a = vec![3, 1, 4, 1, 5, 9, 2, 6, 5];
+        let min = SparseTable::new(a.clone(), |x: i32, y| x.min(y));
+        let max = SparseTable::new(a.clone(), |x: i32, y| x.max(y));
+        for l in 0..a.len() {
+            for r in l + 1..=a.len() {
+                assert_eq!(min.query(l..r), *a[l..r].iter().min().unwrap());
+                assert_eq!(max.query(l..r), *a[l..r].iter().max().unwrap());
+            }
+        }
+        assert_eq!(max.query(..), 9);
+
+        let gcds = SparseTable::new(vec![12, 18, 8, 20], gcd);
+        assert_eq!(gcds.query(0..2), 6);
+        assert_eq!(gcds.query(1..=3), 2);
+        assert_eq!(gcds.query(3..), 20);
+        assert!(SparseTable::new(Vec::<u8>::new(), u8::min).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_empty_query() {
+        SparseTable::new(vec![1, 2, 3], i32::max).query(1..1);
+    }
+}
@@ -0,0 +1,271 @@
+use std::{fs, io, path::Path, time::Duration};
+
+use crate::runner::Limits;
+
+/// One sample test from a problem statement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sample {
+    pub input: String,
+    pub output: String,
+}
+
+/// Extract the sample tests from a Codeforces problem page.
+///
+/// Inputs and outputs are the `<pre>` blocks inside `<div class="input">` and
+/// `<div class="output">`, paired in the order they appear.
+pub fn samples(html: &str) -> Vec<Sample> {
+    let inputs = blocks(html, r#"<div class="input">"#);
+    let outputs = blocks(html, r#"<div class="output">"#);
+    inputs
+        .into_iter()
+        .zip(outputs)
+        .map(|(input, output)| Sample { input, output })
+        .collect()
+}
+
+/// Problem name from the header of a problem page, without its index.
+pub fn title(html: &str) -> Option<String> {
+    let open = r#"<div class="header"><div class="title">"#;
+    let start = html.find(open)? + open.len();
+    let end = start + html[start..].find("</div>")?;
+    let title = strip_tags(&html[start..end]);
+    // e.g. `C. Johnny and Megan's Necklace`.
+    let name = title
+        .split_once(". ")
+        .map_or(title.as_str(), |(_, name)| name);
+    Some(name.trim().to_string())
+}
+
+/// Time and memory limits from the header of a problem page.
+pub fn limits(html: &str) -> Option<Limits> {
+    // e.g. `2 seconds` and `256 megabytes`.
+    let time: f64 = property(html, "time-limit")?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    let memory: u64 = property(html, "memory-limit")?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    Some(Limits::new(
+        Duration::from_millis((time * 1000.0).round() as u64),
+        memory << 20,
+    ))
+}
+
+/// Save samples into `dir` as `1.in`, `1.out`, `2.in`, ...
+pub fn save(dir: &Path, samples: &[Sample]) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    for (i, sample) in samples.iter().enumerate() {
+        fs::write(dir.join(format!("{}.in", i + 1)), &sample.input)?;
+        fs::write(dir.join(format!("{}.out", i + 1)), &sample.output)?;
+    }
+    Ok(())
+}
+
+/// Value of a header property such as `<div class="time-limit">`, without its title.
+fn property(html: &str, class: &str) -> Option<String> {
+    let start = html.find(&format!(r#"<div class="{}">"#, class))?;
+    let rest = &html[start..];
+    let title = rest.find(r#"<div class="property-title">"#)?;
+    let value = title + rest[title..].find("</div>")? + "</div>".len();
+    let end = value + rest[value..].find("</div>")?;
+    Some(strip_tags(&rest[value..end]).trim().to_string())
+}
+
+/// Text of the first `<pre>` after each occurrence of `open`.
+fn blocks(html: &str, open: &str) -> Vec<String> {
+    html.match_indices(open)
+        .filter_map(|(start, _)| {
+            let rest = &html[start..];
+            let pre = rest.find("<pre")?;
+            let body = pre + rest[pre..].find('>')? + 1;
+            let end = body + rest[body..].find("</pre>")?;
+            Some(pre_text(&rest[body..end]))
+        })
+        .collect()
+}
+
+/// Plain text of a `<pre>` block.
+///
+/// Newer statements put every line in its own `<div class="test-example-line">`, older ones
+/// separate lines with `<br />` or plain newlines.
+fn pre_text(pre: &str) -> String {
+    let lines: Vec<String> = if pre.contains("test-example-line") {
+        pre.split("<div")
+            .skip(1)
+            .filter_map(|div| {
+                let body = div.find('>')? + 1;
+                let end = div.find("</div>").unwrap_or(div.len());
+                Some(strip_tags(&div[body..end]))
+            })
+            .collect()
+    } else {
+        strip_tags(
+            &pre.replace("<br />", "\n")
+                .replace("<br/>", "\n")
+                .replace("<br>", "\n"),
+        )
+        .lines()
+        .map(str::to_string)
+        .collect()
+    };
+
+    let lines: Vec<&str> = lines.iter().map(|line| line.trim_end()).collect();
+    let start = lines.iter().position(|line| !line.is_empty());
+    let end = lines.iter().rposition(|line| !line.is_empty());
+    match (start, end) {
+        (Some(start), Some(end)) => lines[start..=end].join("\n") + "\n",
+        _ => String::new(),
+    }
+}
+
+/// Drop tags and decode character references.
+fn strip_tags(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(open) = rest.find('<') {
+        text.push_str(&rest[..open]);
+        rest = match rest[open..].find('>') {
+            Some(close) => &rest[open + close + 1..],
+            None => "",
+        };
+    }
+    text.push_str(rest);
+    decode_entities(&text)
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        decoded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let entity = rest.find(';').map(|end| (&rest[1..end], end));
+        let c = entity.and_then(|(name, _)| match name {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => {
+                let code = match name.strip_prefix("#x").or(name.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => name.strip_prefix('#')?.parse().ok(),
+                };
+                code.and_then(char::from_u32)
+            }
+        });
+        match (c, entity) {
+            (Some(c), Some((_, end))) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_br() {
+        let html = include_str!("../tests/fixtures/statement-4a.html");
+        assert_eq!(
+            samples(html),
+            [Sample {
+                input: "8\n".to_string(),
+                output: "YES\n".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_samples_example_lines() {
+        let html = include_str!("../tests/fixtures/statement-1950a.html");
+        assert_eq!(
+            samples(html),
+            [Sample {
+                input: "7\n1 2 3\n3 2 1\n1 5 3\n3 4 1\n0 0 0\n4 1 7\n4 5 7\n".to_string(),
+                output: "STAIR\nNONE\nPEAK\nPEAK\nNONE\nNONE\nSTAIR\n".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_title() {
+        let html = include_str!("../tests/fixtures/statement-4a.html");
+        assert_eq!(title(html).as_deref(), Some("Watermelon"));
+        let html = include_str!("../tests/fixtures/statement-1950a.html");
+        assert_eq!(title(html).as_deref(), Some("Stair, Peak, or Neither?"));
+        assert_eq!(title("<html></html>"), None);
+    }
+
+    #[test]
+    fn test_limits() {
+        let html = include_str!("../tests/fixtures/statement-4a.html");
+        assert_eq!(
+            limits(html),
+            Some(Limits::new(Duration::from_secs(1), 64 << 20))
+        );
+        let html = include_str!("../tests/fixtures/statement-1950a.html");
+        assert_eq!(
+            limits(html),
+            Some(Limits::new(Duration::from_secs(1), 256 << 20))
+        );
+        assert_eq!(limits("<html></html>"), None);
+    }
+
+    /// Fetches the pages behind the fixtures above and checks the parser against the live site;
+    /// run with `CP_SAVE_FIXTURES=1 cargo test live_pages -- --ignored` to refresh the fixtures.
+    #[tokio::test]
+    #[ignore]
+    async fn test_live_pages() {
+        let html = crate::codeforces::get_problem_page(4, "A").await.unwrap();
+        assert_eq!(
+            samples(&html),
+            [Sample {
+                input: "8\n".to_string(),
+                output: "YES\n".to_string(),
+            }]
+        );
+        let mut pages = vec![("4a", html)];
+
+        // Multi-test problems put each line of a sample in its own `test-example-line` div.
+        let html = crate::codeforces::get_problem_page(1950, "A")
+            .await
+            .unwrap();
+        assert!(html.contains("test-example-line"));
+        let found = samples(&html);
+        assert_eq!(found.len(), 1);
+        let t: usize = found[0].input.lines().next().unwrap().parse().unwrap();
+        assert_eq!(found[0].input.lines().count(), t + 1);
+        assert_eq!(found[0].output.lines().count(), t);
+        pages.push(("1950a", html));
+
+        if std::env::var_os("CP_SAVE_FIXTURES").is_some() {
+            for (name, html) in pages {
+                fs::write(format!("tests/fixtures/statement-{}.html", name), html).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("a &lt; b &amp;&amp; c&#62;d"),
+            "a < b && c>d"
+        );
+        assert_eq!(decode_entities("&#x41;&unknown; & x"), "A&unknown; & x");
+    }
+}
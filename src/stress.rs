@@ -0,0 +1,263 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use crate::checker::Checker;
+use crate::judge::{self, Verdict};
+use crate::runner::{self, Limits};
+use crate::shrink::{self, Granularity};
+use crate::util::TempFile;
+
+/// Number of seeds tried for each smaller size before giving up on it.
+const SEEDS_PER_SIZE: u64 = 20;
+
+/// What happened when both programs ran on one input.
+enum Trial {
+    Agree,
+    Mismatch(Mismatch),
+    BruteFailed(Verdict),
+}
+
+/// A solution, a brute force and a generator, run against each other on random inputs.
+pub struct Stress<'a> {
+    pub solution: PathBuf,
+    pub brute: PathBuf,
+    /// Called as `<generator> <seed> [<size>]`, prints one test input.
+    pub generator: PathBuf,
+    /// Size parameter passed to the generator, which lets failing inputs be shrunk.
+    pub size: Option<u64>,
+    pub checker: &'a dyn Checker,
+    pub limits: Limits,
+}
+
+/// An input on which the solution disagrees with the brute force.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub seed: u64,
+    pub size: Option<u64>,
+    pub input: String,
+    /// Output of the brute force.
+    pub expected: String,
+    /// Output of the solution.
+    pub actual: String,
+    pub verdict: Verdict,
+    pub message: Option<String>,
+}
+
+impl Mismatch {
+    /// Save the failing test into `dir` as `stress-<seed>.{in,out,got}`, returning the input path.
+    ///
+    /// The brute force output becomes the expected answer, so `exec` picks the case up.
+    pub fn save(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let input = dir.join(format!("stress-{}.in", self.seed));
+        fs::write(&input, &self.input)?;
+        fs::write(input.with_extension("out"), &self.expected)?;
+        fs::write(input.with_extension("got"), &self.actual)?;
+        Ok(input)
+    }
+}
+
+impl Stress<'_> {
+    /// Try each seed in order, stopping at the first mismatch.
+    pub fn run(&self, seeds: impl Iterator<Item = u64>) -> io::Result<Option<Mismatch>> {
+        for seed in seeds {
+            print!("\rseed {}", seed);
+            io::stdout().flush()?;
+            let input = self.generate(seed, self.size)?;
+            if let Some(mismatch) = self.test(seed, self.size, &input)? {
+                println!();
+                return Ok(Some(mismatch));
+            }
+        }
+        println!();
+        Ok(None)
+    }
+
+    /// Run the generator with `seed` and `size` and return the input it prints.
+    pub fn generate(&self, seed: u64, size: Option<u64>) -> io::Result<String> {
+        let mut command = Command::new(&self.generator);
+        command.arg(seed.to_string());
+        if let Some(size) = size {
+            command.arg(size.to_string());
+        }
+        let execution = runner::run(&mut command, Stdio::null(), &self.limits)?;
+        if let Some(verdict) = Verdict::from_execution(&execution, &self.limits) {
+            return Err(io::Error::other(format!(
+                "generator failed on seed {}: {}",
+                seed, verdict
+            )));
+        }
+        Ok(String::from_utf8_lossy(&execution.stdout).to_string())
+    }
+
+    /// Run both programs on `input`, returning a mismatch if the solution is not accepted.
+    pub fn test(&self, seed: u64, size: Option<u64>, input: &str) -> io::Result<Option<Mismatch>> {
+        match self.trial(seed, size, input)? {
+            Trial::Agree => Ok(None),
+            Trial::Mismatch(mismatch) => Ok(Some(mismatch)),
+            Trial::BruteFailed(verdict) => Err(io::Error::other(format!(
+                "brute force failed on seed {}: {}",
+                seed, verdict
+            ))),
+        }
+    }
+
+    /// Look for a smaller input that still fails.
+    ///
+    /// With a size parameter the generator is re-run with smaller sizes first; then, given a
+    /// granularity, lines or tokens are removed from the input as long as the mismatch persists.
+    /// Candidates the brute force cannot handle are treated as passing.
+    pub fn shrink(
+        &self,
+        mismatch: Mismatch,
+        granularity: Option<Granularity>,
+    ) -> io::Result<Mismatch> {
+        let mut best = mismatch;
+
+        if let Some(mut size) = best.size {
+            'sizes: loop {
+                let mut smaller = vec![size / 2, size - size / 4, size - 1];
+                smaller.retain(|&candidate| candidate > 0 && candidate < size);
+                smaller.dedup();
+                for candidate in smaller {
+                    for seed in best.seed..best.seed + SEEDS_PER_SIZE {
+                        let input = self.generate(seed, Some(candidate))?;
+                        if let Trial::Mismatch(mismatch) =
+                            self.trial(seed, Some(candidate), &input)?
+                        {
+                            best = mismatch;
+                            size = candidate;
+                            continue 'sizes;
+                        }
+                    }
+                }
+                break;
+            }
+        }
+
+        if let Some(granularity) = granularity {
+            let mut last = None;
+            shrink::ddmin(&best.input, granularity, |candidate| {
+                Ok(match self.trial(best.seed, best.size, candidate)? {
+                    Trial::Mismatch(mismatch) => {
+                        last = Some(mismatch);
+                        true
+                    }
+                    _ => false,
+                })
+            })?;
+            // The result of `ddmin` is the last candidate that failed.
+            if let Some(mismatch) = last {
+                best = mismatch;
+            }
+        }
+
+        Ok(best)
+    }
+
+    fn trial(&self, seed: u64, size: Option<u64>, input: &str) -> io::Result<Trial> {
+        let file = TempFile::new("in", input)?;
+
+        let brute = runner::run(
+            &mut Command::new(&self.brute),
+            Stdio::from(File::open(&file.0)?),
+            &self.limits,
+        )?;
+        if let Some(verdict) = Verdict::from_execution(&brute, &self.limits) {
+            return Ok(Trial::BruteFailed(verdict));
+        }
+        let expected = String::from_utf8_lossy(&brute.stdout).to_string();
+
+        let solution = runner::run(
+            &mut Command::new(&self.solution),
+            Stdio::from(File::open(&file.0)?),
+            &self.limits,
+        )?;
+        let actual = String::from_utf8_lossy(&solution.stdout).to_string();
+
+        let (verdict, message) = judge::judge(
+            &solution,
+            &self.limits,
+            self.checker,
+            &file.0,
+            Some(&expected),
+            &actual,
+        )?;
+        Ok(match verdict {
+            Some(Verdict::Accepted) | None => Trial::Agree,
+            Some(verdict) => Trial::Mismatch(Mismatch {
+                seed,
+                size,
+                input: input.to_string(),
+                expected,
+                actual,
+                verdict,
+                message,
+            }),
+        })
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::checker::Tokens;
+    use crate::test_util::script;
+
+    #[test]
+    fn test_stress() {
+        let generator = script("echo $1");
+        let brute = script("read n; echo $((n * 2))");
+        let solution = script("read n; if [ $n -eq 4 ]; then echo 9; else echo $((n + n)); fi");
+        let checker = Tokens { ignore_case: false };
+        let stress = Stress {
+            solution: solution.0.clone(),
+            brute: brute.0.clone(),
+            generator: generator.0.clone(),
+            size: None,
+            checker: &checker,
+            limits: Limits::default(),
+        };
+
+        let mismatch = stress.run(1..10).unwrap().unwrap();
+        assert_eq!(mismatch.seed, 4);
+        assert_eq!(mismatch.input, "4\n");
+        assert_eq!(mismatch.expected, "8\n");
+        assert_eq!(mismatch.actual, "9\n");
+        assert_eq!(mismatch.verdict, Verdict::WrongAnswer);
+
+        assert_eq!(stress.run(5..10).unwrap(), None);
+    }
+
+    #[test]
+    fn test_shrink() {
+        // Prints `size` numbers; the solution breaks on any input containing a 7.
+        let generator = script("seq $(( $2 - $1 % 3 )) | tr '\\n' ' '; echo");
+        let brute = script("echo ok");
+        let solution = script("if grep -qw 7; then echo fail; else echo ok; fi");
+        let checker = Tokens { ignore_case: false };
+        let stress = Stress {
+            solution: solution.0.clone(),
+            brute: brute.0.clone(),
+            generator: generator.0.clone(),
+            size: Some(100),
+            checker: &checker,
+            limits: Limits::default(),
+        };
+
+        let mismatch = stress.run(1..10).unwrap().unwrap();
+        assert_eq!(mismatch.seed, 1);
+
+        let shrunk = stress.shrink(mismatch.clone(), None).unwrap();
+        assert!(shrunk.size.unwrap() < 20);
+        assert!(shrunk.input.split_whitespace().count() >= 7);
+
+        let shrunk = stress.shrink(mismatch, Some(Granularity::Tokens)).unwrap();
+        assert_eq!(shrunk.input, "7\n");
+        assert_eq!(shrunk.actual, "fail\n");
+    }
+}
@@ -0,0 +1,477 @@
+//! String algorithms on `&[u8]`, `&[char]` or any other slice.
+use std::{
+    collections::hash_map::{DefaultHasher, RandomState},
+    hash::{BuildHasher, Hash, Hasher},
+    ops::RangeBounds,
+    sync::OnceLock,
+};
+
+use crate::segtree::bounds;
+
+/// `pi[i]` is the length of the longest proper prefix of `s[..=i]` that is also its suffix.
+pub fn prefix_function<T: PartialEq>(s: &[T]) -> Vec<usize> {
+    let mut pi = vec![0; s.len()];
+    for i in 1..s.len() {
+        let mut k = pi[i - 1];
+        while k > 0 && s[i] != s[k] {
+            k = pi[k - 1];
+        }
+        if s[i] == s[k] {
+            k += 1;
+        }
+        pi[i] = k;
+    }
+    pi
+}
+
+/// `z[i]` is the length of the longest common prefix of `s` and `s[i..]`, so `z[0] = s.len()`.
+pub fn z_algorithm<T: PartialEq>(s: &[T]) -> Vec<usize> {
+    let n = s.len();
+    let mut z = vec![0; n];
+    if n == 0 {
+        return z;
+    }
+    z[0] = n;
+    // `s[l..r]` is the match found so far reaching furthest right.
+    let (mut l, mut r) = (0, 0);
+    for i in 1..n {
+        let mut k = if i < r { z[i - l].min(r - i) } else { 0 };
+        while i + k < n && s[k] == s[i + k] {
+            k += 1;
+        }
+        z[i] = k;
+        if i + k > r {
+            (l, r) = (i, i + k);
+        }
+    }
+    z
+}
+
+/// Manacher's algorithm: `pal[i]` for `i` in `0..2n - 1` is the length of the longest palindrome
+/// centered at `s[i / 2]` for even `i`, or between `s[i / 2]` and `s[i / 2 + 1]` for odd `i`.
+pub fn manacher<T: PartialEq>(s: &[T]) -> Vec<usize> {
+    let n = s.len();
+    if n == 0 {
+        return vec![];
+    }
+    // Radii on `s` interleaved with separators: `t[2i] = s[i]`, and `t[2i + 1]` is a separator.
+    let m = 2 * n - 1;
+    let mut radius = vec![0; m];
+    let (mut center, mut right) = (0, 0);
+    for i in 0..m {
+        let mut k = if i < right {
+            radius[2 * center - i].min(right - i)
+        } else {
+            0
+        };
+        // Extend while `t[i - k - 1]` and `t[i + k + 1]` are equal characters or both separators.
+        while k < i && i + k + 1 < m {
+            let (a, b) = (i - k - 1, i + k + 1);
+            if b % 2 == 0 && s[a / 2] != s[b / 2] {
+                break;
+            }
+            k += 1;
+        }
+        radius[i] = k;
+        if i + k > right {
+            (center, right) = (i, i + k);
+        }
+    }
+    // A radius on `t` around a character is odd, around a separator even, and equals the length.
+    radius
+        .iter()
+        .enumerate()
+        .map(|(i, &k)| if i % 2 == 0 { k | 1 } else { (k + 1) & !1 })
+        .collect()
+}
+
+/// Start indices of the suffixes of `s` in lexicographic order, by SA-IS in `O(n log n)` for the
+/// coordinate compression and `O(n)` after it.
+pub fn suffix_array<T: Ord>(s: &[T]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..s.len()).collect();
+    order.sort_by(|&i, &j| s[i].cmp(&s[j]));
+    let mut ranks = vec![0; s.len()];
+    let mut upper = 0;
+    for k in 1..order.len() {
+        if s[order[k - 1]] != s[order[k]] {
+            upper += 1;
+        }
+        ranks[order[k]] = upper;
+    }
+    sa_is(&ranks, upper)
+}
+
+/// SA-IS on `s` with values in `0..=upper`.
+fn sa_is(s: &[usize], upper: usize) -> Vec<usize> {
+    let n = s.len();
+    match n {
+        0 => return vec![],
+        1 => return vec![0],
+        2 => return if s[0] < s[1] { vec![0, 1] } else { vec![1, 0] },
+        _ => {}
+    }
+    // `ls[i]` is whether the suffix at `i` is S-type, i.e. smaller than the suffix at `i + 1`.
+    let mut ls = vec![false; n];
+    for i in (0..n - 1).rev() {
+        ls[i] = if s[i] == s[i + 1] {
+            ls[i + 1]
+        } else {
+            s[i] < s[i + 1]
+        };
+    }
+    // The starts of the L-type and S-type buckets of each value.
+    let mut sum_l = vec![0; upper + 1];
+    let mut sum_s = vec![0; upper + 1];
+    for i in 0..n {
+        if !ls[i] {
+            sum_s[s[i]] += 1;
+        } else {
+            sum_l[s[i] + 1] += 1;
+        }
+    }
+    for i in 0..=upper {
+        sum_s[i] += sum_l[i];
+        if i < upper {
+            sum_l[i + 1] += sum_s[i];
+        }
+    }
+
+    let induce = |lms: &[usize]| {
+        let mut sa = vec![usize::MAX; n];
+        let mut buf = sum_s.clone();
+        for &d in lms {
+            sa[buf[s[d]]] = d;
+            buf[s[d]] += 1;
+        }
+        buf.copy_from_slice(&sum_l);
+        sa[buf[s[n - 1]]] = n - 1;
+        buf[s[n - 1]] += 1;
+        for i in 0..n {
+            let v = sa[i];
+            if v != usize::MAX && v >= 1 && !ls[v - 1] {
+                sa[buf[s[v - 1]]] = v - 1;
+                buf[s[v - 1]] += 1;
+            }
+        }
+        buf.copy_from_slice(&sum_l);
+        for i in (0..n).rev() {
+            let v = sa[i];
+            if v != usize::MAX && v >= 1 && ls[v - 1] {
+                buf[s[v - 1] + 1] -= 1;
+                sa[buf[s[v - 1] + 1]] = v - 1;
+            }
+        }
+        sa
+    };
+
+    // Sort the LMS substrings by inducing from the LMS positions in any order.
+    let mut lms_map = vec![usize::MAX; n];
+    let lms: Vec<usize> = (1..n).filter(|&i| !ls[i - 1] && ls[i]).collect();
+    for (k, &i) in lms.iter().enumerate() {
+        lms_map[i] = k;
+    }
+    let m = lms.len();
+    let sa = induce(&lms);
+    if m == 0 {
+        return sa;
+    }
+
+    // Name the LMS substrings by their order, then sort the LMS suffixes recursively.
+    let mut sorted_lms: Vec<usize> = sa
+        .into_iter()
+        .filter(|&v| lms_map[v] != usize::MAX)
+        .collect();
+    let mut rec_s = vec![0; m];
+    let mut rec_upper = 0;
+    for k in 1..m {
+        let (mut l, mut r) = (sorted_lms[k - 1], sorted_lms[k]);
+        let end_l = lms.get(lms_map[l] + 1).copied().unwrap_or(n);
+        let end_r = lms.get(lms_map[r] + 1).copied().unwrap_or(n);
+        let mut same = end_l - l == end_r - r;
+        if same {
+            while l < end_l && s[l] == s[r] {
+                l += 1;
+                r += 1;
+            }
+            if l == n || s[l] != s[r] {
+                same = false;
+            }
+        }
+        if !same {
+            rec_upper += 1;
+        }
+        rec_s[lms_map[sorted_lms[k]]] = rec_upper;
+    }
+    for (k, i) in sa_is(&rec_s, rec_upper).into_iter().enumerate() {
+        sorted_lms[k] = lms[i];
+    }
+    induce(&sorted_lms)
+}
+
+/// Kasai's algorithm: `lcp[i]` is the length of the longest common prefix of the suffixes at
+/// `sa[i]` and `sa[i + 1]`.
+pub fn lcp_array<T: PartialEq>(s: &[T], sa: &[usize]) -> Vec<usize> {
+    let n = s.len();
+    assert_eq!(sa.len(), n);
+    if n == 0 {
+        return vec![];
+    }
+    let mut rank = vec![0; n];
+    for (k, &i) in sa.iter().enumerate() {
+        rank[i] = k;
+    }
+    let mut lcp = vec![0; n - 1];
+    let mut h: usize = 0;
+    for i in 0..n {
+        h = h.saturating_sub(1);
+        if rank[i] == 0 {
+            continue;
+        }
+        let j = sa[rank[i] - 1];
+        while i + h < n && j + h < n && s[i + h] == s[j + h] {
+            h += 1;
+        }
+        lcp[rank[i] - 1] = h;
+    }
+    lcp
+}
+
+const MOD: u64 = (1 << 61) - 1;
+
+fn mul_mod(a: u64, b: u64) -> u64 {
+    let p = a as u128 * b as u128;
+    let x = (p >> 61) as u64 + (p as u64 & MOD);
+    if x >= MOD {
+        x - MOD
+    } else {
+        x
+    }
+}
+
+/// The base shared by all hashes of the process, chosen at random so that fixed anti-hash tests
+/// cannot target it.
+fn base() -> u64 {
+    static BASE: OnceLock<u64> = OnceLock::new();
+    *BASE.get_or_init(|| {
+        let random = RandomState::new().build_hasher().finish();
+        (1 << 20) + random % (MOD - (1 << 21))
+    })
+}
+
+/// A nonzero value modulo `2^61 - 1` standing for `x`, so that leading elements count.
+fn value(x: u64) -> u64 {
+    x % (MOD - 1) + 1
+}
+
+/// `value` of the SipHash of `x`, for elements that are not integers.
+fn hashed_value<T: Hash>(x: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    x.hash(&mut hasher);
+    value(hasher.finish())
+}
+
+/// Polynomial hashes of all substrings modulo `2^61 - 1`, comparable across strings.
+///
+/// `new` takes bytes, chars and unsigned integers as they are; `new_hashed` takes any `Hash`
+/// elements, such as strings or tuples, at the cost of hashing each one first.
+#[derive(Debug, Clone)]
+pub struct RollingHash {
+    /// `hash[i]` is the hash of `s[..i]`.
+    hash: Vec<u64>,
+    /// `pow[i]` is `base^i`.
+    pow: Vec<u64>,
+}
+
+impl RollingHash {
+    pub fn new<T: Copy + Into<u64>>(s: &[T]) -> Self {
+        Self::from_values(s.iter().map(|&x| value(x.into())))
+    }
+
+    pub fn new_hashed<T: Hash>(s: &[T]) -> Self {
+        Self::from_values(s.iter().map(hashed_value))
+    }
+
+    fn from_values(values: impl ExactSizeIterator<Item = u64>) -> Self {
+        let base = base();
+        let mut hash = vec![0; values.len() + 1];
+        let mut pow = vec![1; values.len() + 1];
+        for (i, x) in values.enumerate() {
+            hash[i + 1] = (mul_mod(hash[i], base) + x) % MOD;
+            pow[i + 1] = mul_mod(pow[i], base);
+        }
+        RollingHash { hash, pow }
+    }
+
+    pub fn len(&self) -> usize {
+        self.hash.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Hash of `s[range]`, equal to `hash(&s[range])` or `hash_hashed(&s[range])`.
+    pub fn get(&self, range: impl RangeBounds<usize>) -> u64 {
+        let (l, r) = bounds(range, self.len());
+        (self.hash[r] + MOD - mul_mod(self.hash[l], self.pow[r - l])) % MOD
+    }
+
+    /// Length of the longest common prefix of `s[i..]` and `t[j..]`, where `other` hashes `t`,
+    /// in `O(log n)`.
+    pub fn lcp(&self, i: usize, other: &RollingHash, j: usize) -> usize {
+        let (mut ok, mut ng) = (0, (self.len() - i).min(other.len() - j) + 1);
+        while ng - ok > 1 {
+            let mid = (ok + ng) / 2;
+            if self.get(i..i + mid) == other.get(j..j + mid) {
+                ok = mid;
+            } else {
+                ng = mid;
+            }
+        }
+        ok
+    }
+}
+
+/// Hash of the whole of `s`, for comparing with substrings of `RollingHash::new`.
+pub fn hash<T: Copy + Into<u64>>(s: &[T]) -> u64 {
+    fold(s.iter().map(|&x| value(x.into())))
+}
+
+/// Hash of the whole of `s`, for comparing with substrings of `RollingHash::new_hashed`.
+pub fn hash_hashed<T: Hash>(s: &[T]) -> u64 {
+    fold(s.iter().map(hashed_value))
+}
+
+fn fold(values: impl Iterator<Item = u64>) -> u64 {
+    let base = base();
+    values.fold(0, |h, x| (mul_mod(h, base) + x) % MOD)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic strings over small alphabets, to hit repeats and palindromes often.
+    fn strings() -> Vec<Vec<u8>> {
+        let mut state = 12345u64;
+        let mut result = vec![
+            vec![],
+            b"a".to_vec(),
+            b"aaaaaaaaaa".to_vec(),
+            b"mississippi".to_vec(),
+        ];
+        for len in 1..60 {
+            for alphabet in [2, 3, 26] {
+                let s = (0..len)
+                    .map(|_| {
+                        state = state
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        b'a' + (state >> 33) as u8 % alphabet
+                    })
+                    .collect();
+                result.push(s);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_prefix_function() {
+        assert_eq!(prefix_function(b"abacaba"), [0, 0, 1, 0, 1, 2, 3]);
+        for s in strings() {
+            let pi = prefix_function(&s);
+            for i in 0..s.len() {
+                let expected = (0..=i).rev().find(|&k| s[..k] == s[i + 1 - k..=i]).unwrap();
+                assert_eq!(pi[i], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_z_algorithm() {
+        assert_eq!(z_algorithm(b"aaabaab"), [7, 2, 1, 0, 2, 1, 0]);
+        for s in strings() {
+            let z = z_algorithm(&s);
+            for i in 0..s.len() {
+                let expected = s[i..].iter().zip(&s).take_while(|(a, b)| a == b).count();
+                assert_eq!(z[i], expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_manacher() {
+        assert_eq!(manacher(b"abaab"), [1, 0, 3, 0, 1, 4, 1, 0, 1]);
+        assert_eq!(manacher(&['x', 'x']), [1, 2, 1]);
+        for s in strings() {
+            let pal = manacher(&s);
+            assert_eq!(pal.len(), (2 * s.len()).saturating_sub(1));
+            for (i, &len) in pal.iter().enumerate() {
+                let is_palindrome = |len: usize| {
+                    let l = (i + 1 - len) / 2;
+                    s[l..l + len].iter().eq(s[l..l + len].iter().rev())
+                };
+                let max_len = (i + 1).min(2 * s.len() - 1 - i);
+                let expected = (0..=max_len)
+                    .rev()
+                    .find(|&len| len % 2 == (i + 1) % 2 && is_palindrome(len))
+                    .unwrap_or(0);
+                assert_eq!(len, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_suffix_array() {
+        let sa = suffix_array(b"mississippi");
+        assert_eq!(sa, [10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2]);
+        assert_eq!(
+            lcp_array(b"mississippi", &sa),
+            [1, 1, 4, 0, 0, 1, 0, 2, 1, 3]
+        );
+        assert_eq!(suffix_array(&[3, -1, 3, -1]), [3, 1, 2, 0]);
+        for s in strings() {
+            let mut expected: Vec<usize> = (0..s.len()).collect();
+            expected.sort_by_key(|&i| &s[i..]);
+            let sa = suffix_array(&s);
+            assert_eq!(sa, expected);
+            let lcp = lcp_array(&s, &sa);
+            for (k, &h) in lcp.iter().enumerate() {
+                let (a, b) = (&s[sa[k]..], &s[sa[k + 1]..]);
+                assert_eq!(h, a.iter().zip(b).take_while(|(x, y)| x == y).count());
+            }
+        }
+    }
+
+    #[test]
+    fn test_rolling_hash() {
+        let s = b"abracadabra";
+        let rh = RollingHash::new(s);
+        assert_eq!(rh.len(), 11);
+        assert_eq!(rh.get(0..4), rh.get(7..));
+        assert_eq!(rh.get(0..4), hash(b"abra"));
+        assert_ne!(rh.get(0..4), rh.get(1..5));
+        assert_eq!(rh.get(3..3), hash::<u8>(&[]));
+        assert_eq!(rh.lcp(0, &rh, 7), 4);
+        assert_eq!(rh.lcp(0, &RollingHash::new(b"abrac"), 0), 5);
+
+        let words = ["ab", "cd", "ab"];
+        let wh = RollingHash::new_hashed(&words);
+        assert_eq!(wh.get(..1), wh.get(2..));
+        assert_eq!(wh.get(1..), hash_hashed(&["cd", "ab"]));
+        let chars: Vec<char> = "añña".chars().collect();
+        let ch = RollingHash::new(&chars);
+        assert_eq!(ch.get(1..2), ch.get(2..3));
+        assert_eq!(ch.get(..), hash(&['a', 'ñ', 'ñ', 'a']));
+        // Leading zeros are not ignored.
+        assert_ne!(hash(&[0u8, 1]), hash(&[1u8]));
+        for s in strings() {
+            let rh = RollingHash::new(&s);
+            for i in 0..s.len() {
+                let expected = s[i..].iter().zip(&s).take_while(|(a, b)| a == b).count();
+                assert_eq!(rh.lcp(0, &rh, i), expected);
+            }
+        }
+    }
+}
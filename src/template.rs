@@ -0,0 +1,82 @@
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Replace every `{{key}}` in `template` with its value; unknown placeholders are kept as is.
+pub fn render(template: &str, vars: &BTreeMap<&str, String>) -> String {
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        rendered.push_str(&rest[..open]);
+        rest = &rest[open..];
+        let value = rest
+            .find("}}")
+            .and_then(|close| Some((vars.get(rest[2..close].trim())?, close)));
+        match value {
+            Some((value, close)) => {
+                rendered.push_str(value);
+                rest = &rest[close + 2..];
+            }
+            None => {
+                rendered.push_str("{{");
+                rest = &rest[2..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Today's date in UTC as `YYYY-MM-DD`.
+pub fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("failed to get system time")
+        .as_secs();
+    date(secs / 86400)
+}
+
+/// Format a number of days since 1970-01-01 as a date, using the proleptic Gregorian calendar.
+fn date(days: u64) -> String {
+    // Shift the epoch to 0000-03-01 so leap days fall at the end of each year.
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let vars = BTreeMap::from([
+            ("id", "1361c".to_string()),
+            ("name", "Necklace".to_string()),
+        ]);
+        assert_eq!(
+            render("// {{id}}: {{ name }}\n", &vars),
+            "// 1361c: Necklace\n"
+        );
+        assert_eq!(
+            render("{{url}} {{id}} format!(\"{{}}\") {{", &vars),
+            "{{url}} 1361c format!(\"{{}}\") {{"
+        );
+    }
+
+    #[test]
+    fn test_date() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(11016), "2000-02-29");
+        assert_eq!(date(18414), "2020-06-01");
+    }
+}
@@ -1,9 +1,10 @@
-use std::io;
+use cp_rust::{input, output::Output, puts};
 
 fn main() {
-  let mut num = String::new();
-  io::stdin().read_line(&mut num).unwrap();
-  let n: i32 = num.trim().parse().unwrap();
+  let mut out = Output::stdout();
+  input! {
+    n: i32,
+  }
 
-  println!("{}", n);
+  puts!(out, n);
 }
@@ -0,0 +1,15 @@
+//! Fixtures for the tests of the judging subcommands.
+use std::process::Command;
+
+use crate::util::TempFile;
+
+/// An executable shell script, for standing in for compiled programs in tests.
+pub(crate) fn script(contents: &str) -> TempFile {
+    let file = TempFile::new("sh", &format!("#!/bin/sh\n{}\n", contents)).unwrap();
+    Command::new("chmod")
+        .arg("+x")
+        .arg(&file.0)
+        .status()
+        .unwrap();
+    file
+}
@@ -0,0 +1,96 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+/// Interval between two scans of the watched paths.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Detects saves by polling modification times, so no platform watcher is needed.
+pub struct Watcher {
+    paths: Vec<PathBuf>,
+    snapshot: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl Watcher {
+    /// Watch files and directories; a directory is watched together with the files directly in
+    /// it, but not its subdirectories.
+    pub fn new(paths: Vec<PathBuf>) -> Watcher {
+        let snapshot = snapshot(&paths);
+        Watcher { paths, snapshot }
+    }
+
+    /// Block until a watched file is created, modified or removed.
+    pub fn wait(&mut self) {
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            if snapshot(&self.paths) != self.snapshot {
+                // Editors often save in several steps; let them finish.
+                thread::sleep(POLL_INTERVAL);
+                self.snapshot = snapshot(&self.paths);
+                return;
+            }
+        }
+    }
+}
+
+fn snapshot(paths: &[PathBuf]) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let mut snapshot = Vec::new();
+    for path in paths {
+        // A directory's own modification time changes with any file, including transcripts.
+        if !path.is_dir() {
+            snapshot.push((path.clone(), modified(path)));
+        }
+        if let Ok(entries) = fs::read_dir(path) {
+            let mut entries: Vec<PathBuf> = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| !path.is_dir())
+                .filter(|path| path.extension().map_or(true, |ext| ext != "log"))
+                .collect();
+            entries.sort();
+            snapshot.extend(entries.into_iter().map(|path| {
+                let modified = modified(&path);
+                (path, modified)
+            }));
+        }
+    }
+    snapshot
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Clear the terminal and move the cursor to the top left corner.
+pub fn clear_screen() -> io::Result<()> {
+    use io::Write;
+
+    let mut stdout = io::stdout();
+    stdout.write_all(b"\x1b[2J\x1b[H")?;
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot() {
+        let dir = std::env::temp_dir().join(format!("cp-rust-watch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let paths = vec![dir.clone()];
+        let before = snapshot(&paths);
+
+        fs::write(dir.join("1.log"), "").unwrap();
+        assert_eq!(snapshot(&paths), before);
+        fs::create_dir(dir.join("bin")).unwrap();
+        fs::write(dir.join("bin").join("a.rs"), "").unwrap();
+        assert_eq!(snapshot(&paths), before);
+        fs::write(dir.join("1.in"), "").unwrap();
+        assert_ne!(snapshot(&paths), before);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -12,7 +12,7 @@ pub async fn init() -> Session {
     let mut system = System::new();
     system.refresh_all();
 
-    for (_pid, process) in system.processes() {
+    for process in system.processes().values() {
         if process.name().to_string_lossy().contains("geckodriver") {
             process.kill();
         }
@@ -39,6 +39,8 @@ pub async fn init() -> Session {
         if target_os == "windows" { ".exe" } else { "" }
     );
 
+    // The driver has to outlive this function; it is killed on the next `init`.
+    #[allow(clippy::zombie_processes)]
     Command::new(gecko_binary)
         .spawn()
         .expect("failed to start web driver");